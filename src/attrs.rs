@@ -1,5 +1,5 @@
 use syn::{
-    Expr, Lit, Meta, MetaNameValue,
+    Expr, Lit, Meta, MetaNameValue, Token,
     parse::{Parse, ParseStream},
 };
 
@@ -7,6 +7,24 @@ use syn::{
 pub enum FieldAttribute {
     Skip,
     Rename(String),
+    Version,
+    Guard,
+    CreatedAt,
+    UpdatedAt,
+    CreatedBy,
+    UpdatedBy,
+    SoftDelete,
+    InsertOnly,
+    UpdateOnly,
+    Readonly,
+    Generated,
+    DbDefault,
+    SkipIfNone,
+    BindWith(String),
+    DecodeWith(String),
+    Json,
+    Flatten,
+    Prefix(String),
 }
 
 pub struct FieldAttributes {
@@ -25,20 +43,198 @@ impl Parse for FieldAttributes {
 
             let meta = input.parse::<Meta>()?;
             match meta {
-                Meta::NameValue(MetaNameValue { path, value, .. }) => {
-                    if path.is_ident("rename") {
-                        if let Expr::Lit(expr_lit) = value {
-                            if let Lit::Str(val) = expr_lit.lit {
-                                attrs.push(FieldAttribute::Rename(val.value()))
-                            }
-                        }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("rename") => {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(FieldAttribute::Rename(val.value()))
+                    }
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("prefix") => {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(FieldAttribute::Prefix(val.value()))
+                    }
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. })
+                    if path.is_ident("bind_with") =>
+                {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(FieldAttribute::BindWith(val.value()))
+                    }
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. })
+                    if path.is_ident("decode_with") =>
+                {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(FieldAttribute::DecodeWith(val.value()))
                     }
                 }
                 Meta::Path(path) if path.is_ident("skip") => attrs.push(FieldAttribute::Skip),
+                Meta::Path(path) if path.is_ident("version") => attrs.push(FieldAttribute::Version),
+                Meta::Path(path) if path.is_ident("guard") => attrs.push(FieldAttribute::Guard),
+                Meta::Path(path) if path.is_ident("created_at") => {
+                    attrs.push(FieldAttribute::CreatedAt)
+                }
+                Meta::Path(path) if path.is_ident("updated_at") => {
+                    attrs.push(FieldAttribute::UpdatedAt)
+                }
+                Meta::Path(path) if path.is_ident("created_by") => {
+                    attrs.push(FieldAttribute::CreatedBy)
+                }
+                Meta::Path(path) if path.is_ident("updated_by") => {
+                    attrs.push(FieldAttribute::UpdatedBy)
+                }
+                Meta::Path(path) if path.is_ident("soft_delete") => {
+                    attrs.push(FieldAttribute::SoftDelete)
+                }
+                Meta::Path(path) if path.is_ident("insert_only") => {
+                    attrs.push(FieldAttribute::InsertOnly)
+                }
+                Meta::Path(path) if path.is_ident("update_only") => {
+                    attrs.push(FieldAttribute::UpdateOnly)
+                }
+                Meta::Path(path) if path.is_ident("readonly") => {
+                    attrs.push(FieldAttribute::Readonly)
+                }
+                Meta::Path(path) if path.is_ident("generated") => {
+                    attrs.push(FieldAttribute::Generated)
+                }
+                Meta::Path(path) if path.is_ident("db_default") => {
+                    attrs.push(FieldAttribute::DbDefault)
+                }
+                Meta::Path(path) if path.is_ident("skip_if_none") => {
+                    attrs.push(FieldAttribute::SkipIfNone)
+                }
+                Meta::Path(path) if path.is_ident("json") => attrs.push(FieldAttribute::Json),
+                Meta::Path(path) if path.is_ident("flatten") => attrs.push(FieldAttribute::Flatten),
                 u => panic!("unexpected '{:?}' attribute", u.path()),
             }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
 
         Ok(FieldAttributes { attrs })
     }
 }
+
+#[derive(Debug)]
+pub enum StructAttribute {
+    Tenant(String),
+    RenameAll(String),
+    SkipIfNone,
+    Patch,
+    PatchSerde,
+    Filter,
+    FieldRef,
+    Strict,
+}
+
+pub struct StructAttributes {
+    pub attrs: Vec<StructAttribute>,
+}
+
+impl Parse for StructAttributes {
+    #[inline]
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let mut attrs: Vec<StructAttribute> = vec![];
+
+        loop {
+            if input.is_empty() {
+                break;
+            }
+
+            let meta = input.parse::<Meta>()?;
+            match meta {
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("tenant") => {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(StructAttribute::Tenant(val.value()))
+                    }
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. })
+                    if path.is_ident("rename_all") =>
+                {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(StructAttribute::RenameAll(val.value()))
+                    }
+                }
+                Meta::Path(path) if path.is_ident("skip_if_none") => {
+                    attrs.push(StructAttribute::SkipIfNone)
+                }
+                Meta::Path(path) if path.is_ident("patch") => attrs.push(StructAttribute::Patch),
+                Meta::Path(path) if path.is_ident("patch_serde") => {
+                    attrs.push(StructAttribute::PatchSerde)
+                }
+                Meta::Path(path) if path.is_ident("filter") => attrs.push(StructAttribute::Filter),
+                Meta::Path(path) if path.is_ident("field_ref") => {
+                    attrs.push(StructAttribute::FieldRef)
+                }
+                Meta::Path(path) if path.is_ident("strict") => attrs.push(StructAttribute::Strict),
+                u => panic!("unexpected '{:?}' attribute", u.path()),
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(StructAttributes { attrs })
+    }
+}
+
+#[derive(Debug)]
+pub enum EnumAttribute {
+    AsStr,
+    AsI32,
+    RenameAll(String),
+}
+
+pub struct EnumAttributes {
+    pub attrs: Vec<EnumAttribute>,
+}
+
+impl Parse for EnumAttributes {
+    #[inline]
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let mut attrs: Vec<EnumAttribute> = vec![];
+
+        loop {
+            if input.is_empty() {
+                break;
+            }
+
+            let meta = input.parse::<Meta>()?;
+            match meta {
+                Meta::NameValue(MetaNameValue { path, value, .. })
+                    if path.is_ident("rename_all") =>
+                {
+                    if let Expr::Lit(expr_lit) = value
+                        && let Lit::Str(val) = expr_lit.lit
+                    {
+                        attrs.push(EnumAttribute::RenameAll(val.value()))
+                    }
+                }
+                Meta::Path(path) if path.is_ident("as_str") => attrs.push(EnumAttribute::AsStr),
+                Meta::Path(path) if path.is_ident("as_i32") => attrs.push(EnumAttribute::AsI32),
+                u => panic!("unexpected '{:?}' attribute", u.path()),
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(EnumAttributes { attrs })
+    }
+}
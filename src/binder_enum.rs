@@ -0,0 +1,163 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DataEnum, DeriveInput, Fields, Ident};
+
+use crate::attrs::{EnumAttribute, EnumAttributes, FieldAttribute};
+use crate::{attributes, binder_lists, rename_column, snake_case};
+
+/// `BinderEnum` derive, unit-variant enum is bound as its name (`as_str`, default) or discriminant (`as_i32`)
+pub fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident, data, attrs, ..
+    } = input;
+
+    let mut as_i32 = false;
+    let mut rename_all = None;
+    for attr in enum_attributes(&attrs) {
+        match attr {
+            EnumAttribute::AsStr => as_i32 = false,
+            EnumAttribute::AsI32 => as_i32 = true,
+            EnumAttribute::RenameAll(rule) => rename_all = Some(rule),
+        }
+    }
+
+    let variants = match data {
+        Data::Enum(DataEnum { variants, .. }) => variants,
+        _ => panic!("BinderEnum only supports enum"),
+    };
+
+    let mut idents: Vec<&Ident> = Vec::new();
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    // implicit discriminant is the previous one plus 1, starting from 0
+    let mut base = None;
+    let mut offset = 0i32;
+    for variant in &variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            panic!(
+                "BinderEnum variant '{}' must be a unit variant",
+                variant.ident
+            );
+        }
+        let mut name = match &rename_all {
+            Some(rule) => rename_column(rule, &snake_case(&variant.ident.to_string())),
+            None => variant.ident.to_string(),
+        };
+        for attr in attributes(&variant.attrs) {
+            match attr {
+                FieldAttribute::Rename(val) => name = val,
+                u => panic!("unexpected '{:?}' attribute on variant", u),
+            }
+        }
+        match &variant.discriminant {
+            Some((_, expr)) => {
+                base = Some(expr);
+                offset = 0;
+            }
+            None if idents.is_empty() => {}
+            None => offset += 1,
+        }
+        values.push(match base {
+            Some(expr) => quote! { (#expr) as i32 + #offset },
+            None => quote! { #offset },
+        });
+        idents.push(&variant.ident);
+        names.push(name.trim_matches('`').to_string());
+    }
+    let expected = names.join(", ");
+
+    let (repr, encoded, decoded) = if as_i32 {
+        (
+            quote! { i32 },
+            quote! { &self.as_i32() },
+            quote! { Self::try_from_i32(<i32 as sqlx::Decode<'r, sqlx::MySql>>::decode(value)?) },
+        )
+    } else {
+        (
+            quote! { str },
+            quote! { &self.as_str() },
+            quote! { Self::try_from_str(<&str as sqlx::Decode<'r, sqlx::MySql>>::decode(value)?) },
+        )
+    };
+
+    quote! {
+        impl #ident {
+            /// Variant name, as bound by `as_str`
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#ident::#idents => #names,)*
+                }
+            }
+
+            /// Variant discriminant, as bound by `as_i32`
+            pub fn as_i32(&self) -> i32 {
+                match self {
+                    #(#ident::#idents => #values,)*
+                }
+            }
+
+            /// Parse variant name
+            pub fn try_from_str(value: &str) -> Result<Self, String> {
+                match value {
+                    #(#names => Ok(#ident::#idents),)*
+                    _ => Err(format!(
+                        "invalid {} '{}', expected one of {}",
+                        stringify!(#ident), value, #expected
+                    )),
+                }
+            }
+
+            /// Parse variant discriminant
+            pub fn try_from_i32(value: i32) -> Result<Self, String> {
+                #(
+                    if value == #values {
+                        return Ok(#ident::#idents);
+                    }
+                )*
+                Err(format!(
+                    "invalid {} {}, expected one of {}",
+                    stringify!(#ident), value, #expected
+                ))
+            }
+        }
+
+        impl sqlx::Type<sqlx::MySql> for #ident {
+            fn type_info() -> sqlx::mysql::MySqlTypeInfo {
+                <#repr as sqlx::Type<sqlx::MySql>>::type_info()
+            }
+
+            fn compatible(ty: &sqlx::mysql::MySqlTypeInfo) -> bool {
+                <#repr as sqlx::Type<sqlx::MySql>>::compatible(ty)
+            }
+        }
+
+        impl<'q> sqlx::Encode<'q, sqlx::MySql> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <sqlx::MySql as sqlx::Database>::ArgumentBuffer<'q>,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                sqlx::Encode::<'q, sqlx::MySql>::encode_by_ref(#encoded, buf)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::MySql> for #ident {
+            fn decode(value: sqlx::mysql::MySqlValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+                #decoded.map_err(Into::into)
+            }
+        }
+    }
+    .into()
+}
+
+fn enum_attributes(attrs: &[syn::Attribute]) -> Vec<EnumAttribute> {
+    let mut res = Vec::new();
+
+    for list in binder_lists(attrs) {
+        match list.parse_args::<EnumAttributes>() {
+            Ok(items) => res.extend(items.attrs),
+            Err(e) => panic!("Error parsing enum attributes: {}", e),
+        }
+    }
+
+    res
+}
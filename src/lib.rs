@@ -1,56 +1,2128 @@
 use proc_macro::{self, TokenStream};
-use quote::{format_ident, quote};
-use syn::{AttrStyle, Attribute, DeriveInput, FieldsNamed, Ident, Meta, parse_macro_input};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    AttrStyle, Attribute, DeriveInput, Field, FieldsNamed, FieldsUnnamed, Ident, Index, Member,
+    Meta, MetaList, Type, parse_macro_input,
+};
 
 mod attrs;
-use attrs::{FieldAttribute, FieldAttributes};
+mod binder_enum;
+use attrs::{FieldAttribute, FieldAttributes, StructAttribute, StructAttributes};
+
+/// A struct field that takes part in binding (fields marked `skip` are filtered out).
+struct BinderField<'a> {
+    /// variant name of `FieldEnum`, tuple struct's fields are named by their column
+    ident: Ident,
+    member: Member,
+    ty: &'a Type,
+    column: String,
+    version: bool,
+    guard: bool,
+    created_at: bool,
+    updated_at: bool,
+    created_by: bool,
+    updated_by: bool,
+    soft_delete: bool,
+    insert_only: bool,
+    update_only: bool,
+    readonly: bool,
+    skip_if_none: bool,
+    /// function mapping `&T` to a bindable value
+    bind_with: Option<syn::Path>,
+    /// function mapping a decoded value to `Result<T, E>`
+    decode_with: Option<syn::Path>,
+    /// bound and decoded as `sqlx::types::Json<T>`
+    json: bool,
+    /// columns of a nested binder struct, with this prefix
+    flatten: Option<String>,
+}
+
+impl<'a> BinderField<'a> {
+    fn new(
+        index: usize,
+        field: &'a Field,
+        rename_all: Option<&str>,
+        skip_if_none_all: bool,
+    ) -> Option<Self> {
+        let mut column = field
+            .ident
+            .as_ref()
+            .map(|ident| match rename_all {
+                Some(rule) => rename_column(rule, &ident.to_string()),
+                None => ident.to_string(),
+            })
+            .unwrap_or_default();
+        let mut version = false;
+        let mut guard = false;
+        let mut created_at = false;
+        let mut updated_at = false;
+        let mut created_by = false;
+        let mut updated_by = false;
+        let mut soft_delete = false;
+        let mut insert_only = false;
+        let mut update_only = false;
+        let mut readonly = false;
+        // struct level `skip_if_none` applies to every `Option<_>` field
+        let mut skip_if_none = skip_if_none_all && is_option(&field.ty);
+        let mut bind_with = None;
+        let mut decode_with = None;
+        let mut json = false;
+        let mut flatten = false;
+        let mut prefix = None;
+        for attr in attributes(&field.attrs) {
+            match attr {
+                FieldAttribute::Skip => return None,
+                FieldAttribute::Rename(val) => column = val,
+                FieldAttribute::Version => version = true,
+                FieldAttribute::Guard => guard = true,
+                FieldAttribute::CreatedAt => created_at = true,
+                FieldAttribute::UpdatedAt => updated_at = true,
+                FieldAttribute::CreatedBy => created_by = true,
+                FieldAttribute::UpdatedBy => updated_by = true,
+                FieldAttribute::SoftDelete => soft_delete = true,
+                FieldAttribute::InsertOnly => insert_only = true,
+                FieldAttribute::UpdateOnly => update_only = true,
+                // columns computed or defaulted by database are read but never written
+                FieldAttribute::Readonly
+                | FieldAttribute::Generated
+                | FieldAttribute::DbDefault => readonly = true,
+                FieldAttribute::SkipIfNone => {
+                    if !is_option(&field.ty) {
+                        panic!("`skip_if_none` field {} must be `Option<_>`", index);
+                    }
+                    skip_if_none = true
+                }
+                FieldAttribute::BindWith(val) => bind_with = Some(function_path(index, &val)),
+                FieldAttribute::DecodeWith(val) => decode_with = Some(function_path(index, &val)),
+                FieldAttribute::Json => {
+                    if !cfg!(feature = "json") {
+                        panic!(
+                            "`json` field {} requires `json` feature of sqlx_binder",
+                            index
+                        );
+                    }
+                    json = true
+                }
+                FieldAttribute::Flatten => flatten = true,
+                FieldAttribute::Prefix(val) => prefix = Some(val),
+            }
+        }
+        let flatten = match (flatten, prefix) {
+            (true, _) if field.ident.is_none() => {
+                panic!("Tuple struct field {} can not be `flatten`", index)
+            }
+            (true, prefix) => Some(prefix.unwrap_or_default()),
+            (false, Some(_)) => panic!("`prefix` field {} must be `flatten`", index),
+            (false, None) => None,
+        };
+        if json && (bind_with.is_some() || decode_with.is_some()) {
+            panic!(
+                "`json` field {} can not have `bind_with` nor `decode_with`",
+                index
+            );
+        }
+        let (ident, member) = match &field.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => {
+                if column.is_empty() {
+                    panic!(
+                        "Tuple struct field {} must have `rename` as its column",
+                        index
+                    );
+                }
+                let ident = syn::parse_str::<Ident>(&column).unwrap_or_else(|_| {
+                    panic!(
+                        "Tuple struct field {} column '{}' is not a valid identifier",
+                        index, column
+                    )
+                });
+                (ident, Member::Unnamed(Index::from(index)))
+            }
+        };
+        Some(BinderField {
+            ident,
+            member,
+            ty: &field.ty,
+            column,
+            version,
+            guard,
+            created_at,
+            updated_at,
+            created_by,
+            updated_by,
+            soft_delete,
+            insert_only,
+            update_only,
+            readonly,
+            skip_if_none,
+            bind_with,
+            decode_with,
+            json,
+            flatten,
+        })
+    }
+
+    /// `query.bind(..)` of field's `value`, owned or borrowed
+    fn bind_value(&self, value: impl ToTokens) -> impl ToTokens {
+        if self.flatten.is_some() {
+            return quote! { #value.bind(query) };
+        }
+        match &self.bind_with {
+            Some(path) => quote! { query.bind(#path(&#value)) },
+            None if self.json => quote! { query.bind(sqlx::types::Json(#value)) },
+            None => quote! { query.bind(#value) },
+        }
+    }
+
+    /// `query.bind(..)` of the field's value in `self`, borrowed
+    fn bind_field(&self) -> impl ToTokens {
+        let member = &self.member;
+        match &self.bind_with {
+            Some(path) => quote! { query.bind(#path(&self.#member)) },
+            None if self.json => quote! { query.bind(sqlx::types::Json(&self.#member)) },
+            None => quote! { query.bind(&self.#member) },
+        }
+    }
+}
+
+/// Bind a unit-variant enum as its name (or discriminant with `#[sqlx_binder(as_i32)]`),
+/// by implementing `sqlx::Type`, `sqlx::Encode` and `sqlx::Decode` for MySQL.
+#[proc_macro_derive(BinderEnum, attributes(sqlx_binder))]
+pub fn derive_enum(input: TokenStream) -> TokenStream {
+    binder_enum::expand(parse_macro_input!(input))
+}
 
 #[proc_macro_derive(MySqlBinder, attributes(sqlx_binder))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let DeriveInput { ident, data, .. } = parse_macro_input!(input);
+    let DeriveInput {
+        ident,
+        data,
+        attrs,
+        generics,
+        vis,
+        ..
+    } = parse_macro_input!(input);
+
+    let mut tenant = None;
+    let mut rename_all = None;
+    let mut skip_if_none = false;
+    let mut patch_serde = false;
+    let mut filter = false;
+    let mut field_ref = false;
+    let mut patch = false;
+    let mut strict = false;
+    for attr in struct_attributes(&attrs) {
+        match attr {
+            StructAttribute::Tenant(column) => tenant = Some(column),
+            StructAttribute::RenameAll(rule) => rename_all = Some(rule),
+            StructAttribute::SkipIfNone => skip_if_none = true,
+            StructAttribute::Patch => patch = true,
+            StructAttribute::PatchSerde => patch_serde = true,
+            StructAttribute::Filter => filter = true,
+            StructAttribute::FieldRef => field_ref = true,
+            StructAttribute::Strict => strict = true,
+        }
+    }
+    // `patch_serde` derives serde for `#patchname`, so it implies `patch`
+    patch |= patch_serde;
+
+    let output = match data {
+        syn::Data::Struct(s) => match s.fields {
+            syn::Fields::Named(FieldsNamed {
+                named: struct_fields,
+                ..
+            })
+            | syn::Fields::Unnamed(FieldsUnnamed {
+                unnamed: struct_fields,
+                ..
+            }) => {
+                let mut skipped = vec![];
+                let fields = struct_fields
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, field)| {
+                        let binder_field =
+                            BinderField::new(index, field, rename_all.as_deref(), skip_if_none);
+                        if binder_field.is_none() {
+                            let member = match &field.ident {
+                                Some(ident) => Member::Named(ident.clone()),
+                                None => Member::Unnamed(Index::from(index)),
+                            };
+                            skipped.push((member, &field.ty));
+                        }
+                        binder_field
+                    })
+                    .collect::<Vec<_>>();
+
+                // `flatten` fields splice the columns of their own binder struct with a prefix
+                let has_flatten = fields.iter().any(|f| f.flatten.is_some());
+                // `json` types (ex: `serde_json::Value`) are seldom comparable, so with them `PartialEq` and `PartialOrd`
+                // are implemented by `json_comparisons` (and `patch_eq`) instead of derived
+                let has_json = fields.iter().any(|f| f.json);
+                let (enum_derive, ref_derive, patch_derive) = if has_json {
+                    (
+                        quote! { #[derive(Debug, Clone)] },
+                        quote! { #[derive(Debug, Clone, Copy)] },
+                        quote! { #[derive(Debug, Clone)] },
+                    )
+                } else {
+                    (
+                        quote! { #[derive(Debug, PartialEq, PartialOrd, Clone)] },
+                        quote! { #[derive(Debug, PartialEq, PartialOrd, Clone, Copy)] },
+                        quote! { #[derive(Debug, Clone, PartialEq)] },
+                    )
+                };
+                let own_fields = fields
+                    .iter()
+                    .filter(|f| f.flatten.is_none())
+                    .collect::<Vec<_>>();
+                let flatten_fields = fields
+                    .iter()
+                    .filter(|f| f.flatten.is_some())
+                    .collect::<Vec<_>>();
+
+                let idents_enum = fields.iter().map(|f| &f.ident);
+                let members = own_fields.iter().map(|f| &f.member).collect::<Vec<_>>();
+                let tys_enum = fields
+                    .iter()
+                    .map(|f| match f.flatten {
+                        Some(_) => flatten_type(f.ty, "FieldEnum", None),
+                        None => f.ty.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                let idents_getfield = own_fields.iter().map(|f| &f.column);
+
+                let idents_getenum = own_fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let idents_bind = idents_enum.clone();
+                let binds_enum = fields.iter().map(|f| f.bind_value(quote! { p }));
+                let binds_ref = binds_enum.clone();
+
+                let table_name = snake_case(&ident.to_string());
+                let field_count = own_fields.len();
+                let flatten_tys = flatten_fields.iter().map(|f| f.ty).collect::<Vec<_>>();
+                let flatten_idents = flatten_fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let flatten_members = flatten_fields.iter().map(|f| &f.member).collect::<Vec<_>>();
+                let flatten_prefixes = flatten_fields
+                    .iter()
+                    .map(|f| f.flatten.as_deref().unwrap_or_default())
+                    .collect::<Vec<_>>();
+
+                let enumname = format_ident!("{}{}", ident, "FieldEnum");
+
+                // Generic structs carry their generics to `#enumname`, every bound field type must be bindable.
+                let mut bounded = generics.clone();
+                if !generics.params.is_empty() {
+                    let where_clause = bounded.make_where_clause();
+                    for f in own_fields
+                        .iter()
+                        .filter(|f| f.bind_with.is_none() && !f.json)
+                    {
+                        let ty = f.ty;
+                        where_clause.predicates.push(syn::parse_quote! {
+                            #ty: for<'q> sqlx::Encode<'q, sqlx::MySql> + sqlx::Type<sqlx::MySql> + Clone
+                        });
+                    }
+                }
+                let (impl_generics, ty_generics, where_clause) = bounded.split_for_impl();
+                let ty_turbofish = ty_generics.as_turbofish();
+                let enumtype = quote! { #enumname #ty_generics };
+                // unused generics are held by a `PhantomData` of them
+                let phantom_type = |generics: &syn::Generics| {
+                    let params = generics.params.iter().map(|param| match param {
+                        syn::GenericParam::Lifetime(l) => {
+                            let lifetime = &l.lifetime;
+                            quote! { &#lifetime () }
+                        }
+                        syn::GenericParam::Type(t) => {
+                            let ident = &t.ident;
+                            quote! { fn() -> #ident }
+                        }
+                        syn::GenericParam::Const(c) => {
+                            let ident = &c.ident;
+                            quote! { [(); #ident] }
+                        }
+                    });
+                    quote! { std::marker::PhantomData<(#(#params,)*)> }
+                };
+                // in enums, by a variant that can never be constructed
+                let phantom_variant = |generics: &syn::Generics| {
+                    let ty = phantom_type(generics);
+                    quote! {
+                        #[doc(hidden)]
+                        __phantom(#ty, std::convert::Infallible),
+                    }
+                };
+                let phantom = (!generics.params.is_empty()).then(|| phantom_variant(&generics));
+                let phantom_bind = phantom.as_ref().map(|_| {
+                    quote! { #enumname::__phantom(_, never) => match never {}, }
+                });
+
+                // `#refname` borrows field's values, with `'__binder` as the borrow lifetime
+                let refname = format_ident!("{}{}", ident, "FieldRef");
+                let mut ref_generics = bounded.clone();
+                ref_generics.params.insert(0, syn::parse_quote!('__binder));
+                let (ref_impl_generics, ref_ty_generics, _) = ref_generics.split_for_impl();
+                let reftype = quote! { #refname #ref_ty_generics };
+                let ref_phantom = (!generics.params.is_empty() || fields.is_empty())
+                    .then(|| phantom_variant(&ref_generics));
+                let ref_phantom_bind = ref_phantom.as_ref().map(|_| {
+                    quote! { #refname::__phantom(_, never) => match never {}, }
+                });
+                let idents_ref = fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let tys_ref = fields
+                    .iter()
+                    .map(|f| match f.flatten {
+                        Some(_) => flatten_type(f.ty, "FieldRef", Some(syn::parse_quote!('__binder))),
+                        None => {
+                            let ty = f.ty;
+                            syn::parse_quote!(&'__binder #ty)
+                        }
+                    })
+                    .collect::<Vec<Type>>();
+                let enum_compare = has_json.then(|| {
+                    let variants = fields.iter().zip(&tys_enum).map(|(f, ty)| (&f.ident, ty, f.json));
+                    json_comparisons(&enumname, &bounded, variants.collect(), phantom.is_some())
+                });
+                let ref_compare = has_json.then(|| {
+                    let variants = fields.iter().zip(&tys_ref).map(|(f, ty)| (&f.ident, ty, f.json));
+                    json_comparisons(&refname, &ref_generics, variants.collect(), ref_phantom.is_some())
+                });
+
+                // attributes that a parent can't apply to `flatten` columns (its statements use its own)
+                let flatten_unsupported = tenant.is_some()
+                    || own_fields.iter().any(|f| {
+                        f.version
+                            || f.guard
+                            || f.created_at
+                            || f.updated_at
+                            || f.created_by
+                            || f.updated_by
+                            || f.soft_delete
+                            || f.skip_if_none
+                    });
+                let flatten_unsupported_nested = flatten_tys.iter().map(|ty| quote! { || <#ty>::__FLATTEN_UNSUPPORTED });
+                let flatten_checks = flatten_fields.iter().map(|f| {
+                    let ty = f.ty;
+                    let message = format!(
+                        "`flatten` field `{}` is a struct with `version`, `guard`, `created_at`, `updated_at`, \
+                         `created_by`, `updated_by`, `soft_delete`, `skip_if_none` or `tenant`, which `flatten` can not apply",
+                        f.ident
+                    );
+                    quote! { assert!(!<#ty>::__FLATTEN_UNSUPPORTED, #message); }
+                }).collect::<Vec<_>>();
+                // checked at compile time, in `field_names` of generic structs (which every statement calls)
+                let flatten_check = (!flatten_checks.is_empty()).then(|| quote! { #(#flatten_checks)* });
+                let (flatten_check, flatten_check_generic) = match generics.params.is_empty() {
+                    true => (flatten_check.map(|check| quote! { const _: () = { #check }; }), None),
+                    false => (None, flatten_check.map(|check| quote! { const { #check } })),
+                };
+                // every column of the struct in order, `flatten` columns from the nested struct
+                let (field_names, get_field_enums, get_field_refs) = if has_flatten {
+                    let names = fields.iter().map(|f| match &f.flatten {
+                        Some(prefix) => {
+                            let ty = f.ty;
+                            quote! {
+                                names.extend(<#ty>::field_names().iter().map(|name| {
+                                    // a quoted nested column is quoted again as a whole after the prefix
+                                    let column = [#prefix, name.trim_matches('`')].concat();
+                                    let column = match column.contains('-') {
+                                        true => format!("`{}`", column),
+                                        false => column,
+                                    };
+                                    &*Box::leak(column.into_boxed_str())
+                                }));
+                            }
+                        }
+                        None => {
+                            let column = &f.column;
+                            quote! { names.push(#column); }
+                        }
+                    });
+                    let enums = fields.iter().map(|f| {
+                        let (ident, member) = (&f.ident, &f.member);
+                        match f.flatten {
+                            Some(_) => quote! {
+                                self.#member.get_field_enums().into_iter().map(#enumname::#ident)
+                            },
+                            None => {
+                                quote! { std::iter::once(#enumname::#ident(self.#member.clone())) }
+                            }
+                        }
+                    });
+                    let refs = fields.iter().map(|f| {
+                        let (ident, member) = (&f.ident, &f.member);
+                        match f.flatten {
+                            Some(_) => quote! {
+                                self.#member.get_field_refs().into_iter().map(#refname::#ident)
+                            },
+                            None => quote! { std::iter::once(#refname::#ident(&self.#member)) },
+                        }
+                    });
+                    (
+                        quote! {
+                            #flatten_check_generic
+                            static FIELD_NAMES: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+                            FIELD_NAMES.get_or_init(|| {
+                                let mut names = Vec::with_capacity(Self::FIELD_COUNT);
+                                #(#names)*
+                                names
+                            })
+                        },
+                        quote! { std::iter::empty()#(.chain(#enums))*.collect() },
+                        quote! { std::iter::empty()#(.chain(#refs))*.collect() },
+                    )
+                } else {
+                    (
+                        quote! { Self::FIELD_NAMES },
+                        quote! { vec![#(#enumname::#idents_getenum(self.#members.clone())),*] },
+                        quote! { vec![#(#refname::#idents_getenum(&self.#members)),*] },
+                    )
+                };
+                // `#refname` and its methods are only generated with `#[sqlx_binder(field_ref)]`
+                let (get_ref, get_field_refs, field_ref_items) = if field_ref {
+                    (
+                        quote! {
+                            /// Same as `get_enum`, but borrow the field's value instead of cloning it
+                            pub fn get_ref<'__binder>(&'__binder self, field_string: &str) -> Result<#reftype, String> {
+                                match field_string {
+                                    #(stringify!(#idents_getenum) => {
+                                        Ok(#refname::#idents_getenum(&self.#members))
+                                    }),*
+                                    _ => {
+                                        #(
+                                            if let Some(Ok(value)) = field_string
+                                                .strip_prefix(#flatten_prefixes)
+                                                .map(|name| self.#flatten_members.get_ref(name))
+                                            {
+                                                return Ok(#refname::#flatten_idents(value));
+                                            }
+                                        )*
+                                        Err(format!("invalid field name to getref '{}'", field_string))
+                                    }
+                                }
+                            }
+                        },
+                        quote! {
+                            /// Same as `get_field_enums`, but borrow the field's values instead of cloning them
+                            pub fn get_field_refs<'__binder>(&'__binder self) -> Vec<#reftype> {
+                                #get_field_refs
+                            }
+                        },
+                        quote! {
+                            #ref_derive
+                            #[allow(non_camel_case_types)]
+                            pub enum #refname #ref_impl_generics #where_clause {
+                                #(#idents_ref(#tys_ref),)*
+                                #ref_phantom
+                            }
+
+                            impl #ref_impl_generics #reftype #where_clause {
+                                pub fn bind<'q>(
+                                    self,
+                                    query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                                ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>
+                                where
+                                    Self: 'q,
+                                {
+                                    match self {
+                                        #(#refname::#idents_ref(p) => #binds_ref,)*
+                                        #ref_phantom_bind
+                                    }
+                                }
+                            }
+
+                            #ref_compare
+                        },
+                    )
+                } else {
+                    Default::default()
+                };
+                // prefixed names of `flatten` columns can't be concatenated in a const,
+                // so such structs have no `FIELD_NAMES`, only `field_names()`
+                let field_names_const = (!has_flatten).then(|| {
+                    quote! {
+                        /// Column names, the same as `get_field_names`
+                        pub const FIELD_NAMES: &'static [&'static str] = &[#(#idents_getfield),*];
+                    }
+                });
+
+                // `from_row` bounds mention `'r`, so a field type that can't be decoded
+                // (or a skipped field type without `Default`) only disables `from_row`
+                let decode_field = |f: &BinderField, prefixed: bool| {
+                    let member = &f.member;
+                    if let Some(flatten) = &f.flatten {
+                        let ty = f.ty;
+                        let prefix = match prefixed {
+                            true => quote! { &[prefix, #flatten].concat() },
+                            false => quote! { #flatten },
+                        };
+                        return quote! { #member: <#ty>::from_row_with_prefix(row, #prefix)? };
+                    }
+                    let column = f.column.trim_matches('`');
+                    let column = match prefixed {
+                        true => quote! { [prefix, #column].concat().as_str() },
+                        false => quote! { #column },
+                    };
+                    match &f.decode_with {
+                        Some(path) => quote! {
+                            #member: Self::map_decode_error(#column, #path(sqlx::Row::try_get(row, #column)?))?
+                        },
+                        None if f.json => quote! {
+                            #member: sqlx::Row::try_get::<sqlx::types::Json<_>, _>(row, #column)?.0
+                        },
+                        None => quote! { #member: sqlx::Row::try_get(row, #column)? },
+                    }
+                };
+                let decode_fields = fields.iter().map(|f| decode_field(f, false));
+                let map_decode_error = fields.iter().any(|f| f.decode_with.is_some()).then(|| {
+                    quote! {
+                        /// Result of a `decode_with` function for `column`, its error as `sqlx::Error::ColumnDecode`
+                        fn map_decode_error<T, E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+                            column: &str,
+                            result: Result<T, E>,
+                        ) -> sqlx::Result<T> {
+                            result.map_err(|e| sqlx::Error::ColumnDecode {
+                                index: column.to_string(),
+                                source: e.into(),
+                            })
+                        }
+                    }
+                });
+                let decode_prefixed_fields = fields.iter().map(|f| decode_field(f, true));
+                let decode_bounds =
+                    own_fields
+                        .iter()
+                        .filter(|f| f.decode_with.is_none())
+                        .map(|f| {
+                            let ty = f.ty;
+                            let ty = match f.json {
+                                true => quote! { sqlx::types::Json<#ty> },
+                                false => quote! { #ty },
+                            };
+                            quote! { #ty: sqlx::Decode<'r, sqlx::MySql> + sqlx::Type<sqlx::MySql>, }
+                        });
+                let decode_bounds = decode_bounds.collect::<Vec<_>>();
+                let skipped_members = skipped.iter().map(|(member, _)| member).collect::<Vec<_>>();
+                let skipped_tys = skipped.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+                // the same bounds for every `'r`, for methods that decode the rows they fetch
+                let fetch_bounds = quote! {
+                    #(for<'r> #decode_bounds)*
+                    #(for<'r> (#skipped_tys, std::marker::PhantomData<&'r ()>): Default,)*
+                };
+
+                let errname = format_ident!("{}{}", ident, "BinderError");
+
+                let mut versions = fields.iter().filter(|f| f.version);
+                let version = versions.next();
+                if versions.next().is_some() {
+                    panic!("Only one `version` field is supported");
+                }
+                if let Some(f) = version
+                    && !is_integer(f.ty)
+                {
+                    panic!("`version` field '{}' must be an integer type", f.ident);
+                }
+
+                // With a `version` field, update takes `&mut self` (the field is bumped on success),
+                // adds `version=version+1` to SET and `AND version=?` to WHERE,
+                // and returns `#errname::VersionConflict` when no row matched.
+                let (update_self, update_result) = match version {
+                    Some(_) => (
+                        quote! { &mut self },
+                        quote! { Result<MySqlQueryResult, #errname> },
+                    ),
+                    None => (quote! { &self }, quote! { sqlx::Result<MySqlQueryResult> }),
+                };
+                let version_where = version
+                    .map(|f| format!(" AND {}=?", f.column))
+                    .unwrap_or_default();
+
+                // With `tenant`, every method takes a `tenant` value that is written by insert
+                // and added to `WHERE` of update/delete, the field (if any) is never bound.
+                let tenant_arg = tenant.as_ref().map(|_| quote! { tenant: &str, });
+                let tenant_pass = tenant.as_ref().map(|_| quote! { tenant, });
+                let tenant_insert = tenant.as_ref().map(|column| {
+                    quote! {
+                        keys.push(#column);
+                        values.push("?");
+                    }
+                });
+                let tenant_where = tenant
+                    .as_ref()
+                    .map(|column| format!(" AND {}=?", column))
+                    .unwrap_or_default();
+                let tenant_bind = tenant
+                    .as_ref()
+                    .map(|_| quote! { query = query.bind(tenant); });
+
+                // `flatten` columns that the nested struct skips (ex: its `readonly` columns), by their unprefixed name
+                let flatten_skips = |skips: &str| {
+                    let skips = format_ident!("{}", skips);
+                    fields
+                        .iter()
+                        .filter_map(|f| {
+                            let (prefix, ty) = (f.flatten.as_ref()?, f.ty);
+                            Some(quote! { column.trim_matches('`').strip_prefix(#prefix).is_some_and(<#ty>::#skips) })
+                        })
+                        .collect::<Vec<_>>()
+                };
+                // a nested struct's own skips, for structs flattening this one
+                let nested_skips = |skip: fn(&BinderField) -> bool, skips: &str| {
+                    let columns = own_fields
+                        .iter()
+                        .filter(|f| skip(f))
+                        .map(|f| f.column.trim_matches('`'));
+                    let nested = flatten_skips(skips);
+                    quote! { [#(#columns),*].contains(&column) #(|| #nested)* }
+                };
+                let insert_skips = nested_skips(|f| f.update_only || f.readonly, "__insert_skips");
+                let update_skips = nested_skips(|f| f.insert_only || f.readonly, "__update_skips");
+                let not_column = |f: &BinderField| {
+                    let column = &f.column;
+                    quote! { column != #column }
+                };
+                // `insert` binds every column from the struct's values except the primary key (when given),
+                // `created_at` and `updated_at` columns (written as `NOW()`), `update_only` and `readonly` columns,
+                // `created_by` and `updated_by` columns with an actor (bound to `actor` by insert_as),
+                // the tenant column (bound to `tenant`) and `skip_if_none` columns while `None` (so column's DEFAULT applies)
+                let mut insert_binds = vec![quote! { primary_key != Some(column) }];
+                insert_binds.extend(
+                    fields
+                        .iter()
+                        .filter(|f| f.created_at || f.updated_at || f.update_only || f.readonly)
+                        .map(not_column),
+                );
+                insert_binds.extend(fields.iter().filter(|f| f.created_by || f.updated_by).map(|f| {
+                    let column = &f.column;
+                    quote! { !(actor && column == #column) }
+                }));
+                insert_binds.extend(tenant.iter().map(|column| quote! { column != #column }));
+                insert_binds.extend(fields.iter().filter(|f| f.skip_if_none).map(|f| {
+                    let (column, member) = (&f.column, &f.member);
+                    quote! { !(column == #column && self.#member.is_none()) }
+                }));
+                insert_binds.extend(flatten_skips("__insert_skips").into_iter().map(|skip| quote! { !#skip }));
+                let insert_now = fields
+                    .iter()
+                    .filter(|f| f.created_at || f.updated_at)
+                    .map(|f| &f.column);
+                let insert_none_key = fields.iter().filter(|f| f.skip_if_none).map(|f| {
+                    let member = &f.member;
+                    let column = &f.column;
+                    quote! { if self.#member.is_none() { #column } else { "" } }
+                });
+                let insert_audit = fields
+                    .iter()
+                    .filter(|f| f.created_by || f.updated_by)
+                    .map(|f| &f.column)
+                    .collect::<Vec<_>>();
+                let update_audit = fields
+                    .iter()
+                    .filter(|f| f.updated_by)
+                    .map(|f| format!("{}=?", f.column))
+                    .collect::<Vec<_>>();
+                let insert_audit_bind = insert_audit
+                    .iter()
+                    .map(|_| quote! { query = query.bind(actor); });
+                let update_audit_bind = update_audit
+                    .iter()
+                    .map(|_| quote! { query = query.bind(actor); });
+                // `actor` is only read by the `*_binds` of structs with audit columns
+                let (insert_actor_param, update_actor_param) = (
+                    match insert_audit.is_empty() {
+                        true => quote! { _actor },
+                        false => quote! { actor },
+                    },
+                    match update_audit.is_empty() {
+                        true => quote! { _actor },
+                        false => quote! { actor },
+                    },
+                );
+                // `update` SETs every column from the struct's values except the primary key,
+                // `version`, `guard`, `created_at`, `updated_at`, `created_by`, `soft_delete`, `insert_only` and `readonly` columns,
+                // `updated_by` columns with an actor (bound to `actor` by update_as) and the tenant column,
+                // `version` and `updated_at` are SET by expression instead of binding
+                let mut update_binds = vec![quote! { column != primary_key }];
+                update_binds.extend(
+                    fields
+                        .iter()
+                        .filter(|f| {
+                            f.version
+                                || f.guard
+                                || f.created_at
+                                || f.updated_at
+                                || f.created_by
+                                || f.soft_delete
+                                || f.insert_only
+                                || f.readonly
+                        })
+                        .map(not_column),
+                );
+                update_binds.extend(fields.iter().filter(|f| f.updated_by).map(|f| {
+                    let column = &f.column;
+                    quote! { !(actor && column == #column) }
+                }));
+                update_binds.extend(tenant.iter().map(|column| quote! { column != #column }));
+                update_binds.extend(flatten_skips("__update_skips").into_iter().map(|skip| quote! { !#skip }));
+                let set_expressions = fields
+                    .iter()
+                    .filter_map(|f| {
+                        if f.version {
+                            Some(format!("{0}={0}+1", f.column))
+                        } else if f.updated_at {
+                            Some(format!("{}=NOW()", f.column))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let version_bind = version.map(|f| {
+                    let vident = &f.member;
+                    quote! { query = query.bind(self.#vident); }
+                });
+                let guard_where = fields
+                    .iter()
+                    .filter(|f| f.guard)
+                    .map(|f| format!(" AND {}=?", f.column))
+                    .collect::<String>();
+                let guard_bind = fields.iter().filter(|f| f.guard).map(|f| {
+                    let bind = f.bind_field();
+                    quote! { query = #bind; }
+                });
+                let guard_bind_delete = guard_bind.clone();
+                let guard_bind_patch = guard_bind.clone();
+                let binds_columns = fields.iter().map(|f| {
+                    let member = &f.member;
+                    match f.flatten {
+                        Some(_) => quote! { query = self.#member.__bind_columns(query, keep); },
+                        None => {
+                            let bind = f.bind_field();
+                            quote! {
+                                if keep() {
+                                    query = #bind;
+                                }
+                            }
+                        }
+                    }
+                });
+
+                let update_execute = |executor: syn::Ident| match version {
+                    Some(f) => {
+                        let vident = &f.member;
+                        quote! {
+                            let result = query.execute(#executor).await?;
+                            if result.rows_affected() == 0 {
+                                return Err(#errname::VersionConflict);
+                            }
+                            self.#vident += 1;
+                            Ok(result)
+                        }
+                    }
+                    None => quote! { query.execute(#executor).await },
+                };
+                let update_patch_execute = update_execute(format_ident!("pool"));
+                let update_execute = update_execute(format_ident!("executor"));
+                let (version_save, version_restore) = match version {
+                    Some(f) => {
+                        let vident = &f.member;
+                        (
+                            Some(quote! { let version = self.#vident; }),
+                            Some(quote! { if outcome.is_err() { self.#vident = version; } }),
+                        )
+                    }
+                    None => (None, None),
+                };
+
+                let mut soft_deletes = fields.iter().filter(|f| f.soft_delete);
+                let soft_delete = soft_deletes.next();
+                if soft_deletes.next().is_some() {
+                    panic!("Only one `soft_delete` field is supported");
+                }
+                let guard_bind_soft_delete = guard_bind.clone();
+                let soft_delete_methods = soft_delete.map(|f| {
+                    // bool marker is set to 1/0, nullable marker is set to NOW()/NULL
+                    let (deleted, restored) = if is_bool(f.ty) {
+                        ("1", "0")
+                    } else if is_option(f.ty) {
+                        ("NOW()", "NULL")
+                    } else {
+                        panic!("`soft_delete` field '{}' must be `bool` or `Option<_>`", f.ident);
+                    };
+                    let set_deleted = format!("{}={}", f.column, deleted);
+                    let set_restored = format!("{}={}", f.column, restored);
+                    quote! {
+                        /// Mark the row as deleted by setting `soft_delete` column, `WHERE` is the same as `delete`.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)<br>
+                        /// - custom_table_name = `Some("some_table_name")`
+                        pub async fn soft_delete(
+                            &self,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
+                            self.set_soft_delete(true, primary_key, custom_table_name, #tenant_pass pool, db_name).await
+                        }
+
+                        /// Clear `soft_delete` column of a soft-deleted row, `WHERE` is the same as `delete`.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)<br>
+                        /// - custom_table_name = `Some("some_table_name")`
+                        pub async fn restore(
+                            &self,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
+                            self.set_soft_delete(false, primary_key, custom_table_name, #tenant_pass pool, db_name).await
+                        }
+
+                        async fn set_soft_delete(
+                            &self,
+                            deleted: bool,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
+
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let sql = Self::soft_delete_sql(deleted, primary_key, tbname, db_name)?;
+
+                            let mut query = sqlx::query(&sql);
+                            query = self.bind_column(query, primary_key);
+                            #(#guard_bind_soft_delete)*
+                            #tenant_bind
+                            query.execute(pool).await
+                        }
+
+                        /// SQL of `soft_delete` (`deleted`) or `restore`, built once per table and arguments
+                        fn soft_delete_sql(
+                            deleted: bool,
+                            primary_key: &str,
+                            tbname: &str,
+                            db_name: &str,
+                        ) -> sqlx::Result<std::sync::Arc<str>> {
+                            Self::check_column(primary_key)?;
+                            static SOFT_DELETE: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            static RESTORE: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            let (statement, set) = match deleted {
+                                true => (&SOFT_DELETE, #set_deleted),
+                                false => (&RESTORE, #set_restored),
+                            };
+                            Ok(Self::cached_sql(statement, &["soft_delete", set, primary_key, tbname, db_name], || [
+                                "UPDATE ", db_name, ".", tbname, " SET ", set,
+                                " WHERE ", primary_key, "=?", #guard_where, #tenant_where, ";"
+                            ].join("")))
+                        }
+                    }
+                });
+
+                let has_audit = fields.iter().any(|f| f.created_by || f.updated_by);
+                let insert_as = has_audit.then(|| {
+                    quote! {
+                        /// Same as `insert`, but bind `actor` to every `created_by` and `updated_by` column
+                        /// instead of the field's value.
+                        #[allow(clippy::too_many_arguments)]
+                        pub async fn insert_as(
+                            &self,
+                            actor: &str,
+                            primary_key: Option<&str>,
+                            custom_table_name: Option<&str>,
+                            extra_column: &str,
+                            extra_statement: &str,
+                            extra_values: &[&str],
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
+                            self.insert_with_actor(Some(actor), primary_key, custom_table_name, extra_column, extra_statement, extra_values, #tenant_pass pool, db_name).await
+                        }
+                    }
+                });
+                let update_as = has_audit.then(|| {
+                    quote! {
+                        /// Same as `update`, but bind `actor` to every `updated_by` column
+                        /// instead of the field's value.
+                        #[allow(clippy::too_many_arguments)]
+                        pub async fn update_as(
+                            #update_self,
+                            actor: &str,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            extra_column: &str,
+                            extra_values: &[&str],
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> #update_result {
+                            self.update_with_actor(Some(actor), primary_key, custom_table_name, extra_column, extra_values, #tenant_pass pool, db_name).await
+                        }
+                    }
+                });
+
+                // `#patchname` holds an `Option` of every column that update may SET (tenant column excluded),
+                // `update_patch` only SET the columns that are `Some`
+                let patchname = format_ident!("{}{}", ident, "Patch");
+                let patchtype = quote! { #patchname #ty_generics };
+                let patch_fields = fields
+                    .iter()
+                    .filter(|f| {
+                        !(f.version
+                            || f.guard
+                            || f.created_at
+                            || f.updated_at
+                            || f.created_by
+                            || f.soft_delete
+                            || f.insert_only
+                            || f.readonly
+                            || f.flatten.is_some()
+                            || tenant.as_ref() == Some(&f.column))
+                    })
+                    .collect::<Vec<_>>();
+                let idents_patch = patch_fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let members_patch = patch_fields.iter().map(|f| &f.member);
+                let tys_patch = patch_fields.iter().map(|f| f.ty);
+                let columns_patch = patch_fields.iter().map(|f| &f.column);
+                let set_expressions_patch = set_expressions.clone();
+                let binds_patch = patch_fields.iter().map(|f| {
+                    let bind = f.bind_value(quote! { value });
+                    quote! { query = #bind; }
+                });
+                let phantom_key = phantom.as_ref().map(|_| {
+                    quote! { #enumname::__phantom(_, never) => match *never {}, }
+                });
+                let patch_phantom = (!generics.params.is_empty()).then(|| phantom_type(&generics));
+                let patch_phantom_field = patch_phantom.as_ref().map(|ty| {
+                    let serde_skip = patch_serde.then(|| quote! { #[serde(skip)] });
+                    quote! {
+                        #[doc(hidden)]
+                        #serde_skip
+                        pub __phantom: #ty,
+                    }
+                });
+                let patch_phantom_default = patch_phantom
+                    .as_ref()
+                    .map(|_| quote! { __phantom: std::marker::PhantomData, });
+                // a present JSON `null` of an `Option<_>` column is `Some(None)` (SET NULL), a missing key is `None`
+                let patch_derive_serde = patch_serde.then(
+                    || quote! { #[derive(serde::Serialize, serde::Deserialize)] #[serde(default)] },
+                );
+                let deserialize_some = format!(
+                    "{}::__deserialize_some",
+                    quote! { #patchname #ty_turbofish }
+                );
+                let patch_field_serde = patch_fields.iter().map(|f| {
+                    patch_serde.then(|| {
+                        // serde infers no bound for a `deserialize_with` field, so it is given explicitly
+                        let deserialize_with = is_option(f.ty).then(|| {
+                            let ty = f.ty;
+                            let bound = quote! { #ty: serde::Deserialize<'de> }.to_string();
+                            quote! { #[serde(deserialize_with = #deserialize_some, bound(deserialize = #bound))] }
+                        });
+                        quote! {
+                            #[serde(skip_serializing_if = "Option::is_none")]
+                            #deserialize_with
+                        }
+                    })
+                });
+                let patch_deserialize_some = (patch_serde && patch_fields.iter().any(|f| is_option(f.ty))).then(|| {
+                    quote! {
+                        impl #impl_generics #patchtype #where_clause {
+                            fn __deserialize_some<'de, __D, __V>(deserializer: __D) -> Result<Option<__V>, __D::Error>
+                            where
+                                __D: serde::Deserializer<'de>,
+                                __V: serde::Deserialize<'de>,
+                            {
+                                serde::Deserialize::deserialize(deserializer).map(Some)
+                            }
+                        }
+                    }
+                });
+
+                // `#filtermod` (ex: `dog_binder`) holds `Filter`, which renders a parameterised `WHERE` from typed
+                // column conditions (soft-deleted rows are left out unless `with_deleted`), and the other `filter` types,
+                // so `#[sqlx_binder(filter)]` claims no `Dog*` name next to the struct
+                let filtermod = format_ident!("{}_binder", table_name);
+                let filtertype = quote! { #filtermod::Filter #ty_generics };
+                // per-column methods live on `FilterColumns` (`filter.col().age()`), so a field never
+                // collides with a method of `Filter` (ex: `group`, `or`, `new`)
+                let columnstype = quote! { #filtermod::FilterColumns #ty_generics };
+                let mut column_generics = bounded.clone();
+                column_generics.params.push(syn::parse_quote!(__V));
+                let (column_impl_generics, column_ty_generics, _) =
+                    column_generics.split_for_impl();
+                let generic_args = generics
+                    .params
+                    .iter()
+                    .map(|param| match param {
+                        syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+                        syn::GenericParam::Type(t) => t.ident.to_token_stream(),
+                        syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+                    })
+                    .collect::<Vec<_>>();
+                let column_methods = own_fields.iter().map(|f| {
+                    let (ident, ty, column) = (&f.ident, f.ty, &f.column);
+                    quote! {
+                        pub fn #ident(self) -> #filtermod::FilterColumn<#(#generic_args,)* #ty> {
+                            #filtermod::FilterColumn {
+                                filter: self.filter,
+                                column: #column,
+                                value: #enumname::#ident,
+                            }
+                        }
+                    }
+                });
+                let soft_delete_live = soft_delete.map(|f| match is_bool(f.ty) {
+                    true => format!("{}=0", f.column),
+                    false => format!("{} IS NULL", f.column),
+                });
+                let (
+                    with_deleted_field,
+                    with_deleted_default,
+                    with_deleted_method,
+                    soft_delete_condition,
+                ) = match &soft_delete_live {
+                    Some(live) => (
+                        Some(quote! { pub(super) with_deleted: bool, }),
+                        Some(quote! { with_deleted: false, }),
+                        Some(quote! {
+                            /// Include soft-deleted rows
+                            pub fn with_deleted(mut self) -> Self {
+                                self.with_deleted = true;
+                                self
+                            }
+                        }),
+                        Some(quote! {
+                            if !with_deleted {
+                                conditions.push(#live.to_string());
+                            }
+                        }),
+                    ),
+                    None => (None, None, None, None),
+                };
+                let tenant_condition = tenant.as_ref().map(|column| {
+                    let condition = format!("{}=?", column);
+                    quote! { conditions.push(#condition.to_string()); }
+                });
+                // `Column` names a column for `ORDER BY`, `Page` is a LIMIT/OFFSET or keyset page
+                let pagetype = quote! { #filtermod::Page #ty_generics };
+                let (with_deleted_param, filter_with_deleted) = match soft_delete {
+                    Some(_) => (quote! { with_deleted }, quote! { filter.with_deleted }),
+                    None => (quote! { _with_deleted }, quote! { false }),
+                };
+                let idents_column = own_fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let columns_column = own_fields.iter().map(|f| &f.column);
+                let tenant_bind_page = tenant_bind.clone();
+                let tenant_bind_filter = tenant_bind.clone();
+                // `stream_all` returns a `futures_core` stream, so it needs `stream` feature (and `futures-core` in the user crate)
+                let stream_all = cfg!(feature = "stream").then(|| {
+                    let tenant_bind_stream = tenant_bind.clone();
+                    quote! {
+                        /// Stream every row matching `filter` from `executor`, decoding one row at a time,
+                        /// for exports and batch jobs that should not load a whole table into memory.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                        pub fn stream_all<'e, 'c: 'e, E>(
+                            filter: &#filtertype,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            executor: E,
+                            db_name: &str,
+                        ) -> futures_core::stream::BoxStream<'e, sqlx::Result<Self>>
+                        where
+                            E: 'e + sqlx::Executor<'c, Database = sqlx::MySql>,
+                            Self: Send + Unpin + 'e,
+                            #fetch_bounds
+                        {
+                            use std::task::Poll;
+
+                            /// Rows handed over one at a time by `future`, which owns the SQL text
+                            /// (and the `fetch` stream borrowing it)
+                            struct RowStream<F, T> {
+                                future: std::pin::Pin<Box<F>>,
+                                slot: std::sync::Arc<std::sync::Mutex<Option<T>>>,
+                                done: bool,
+                            }
+
+                            impl<F: std::future::Future<Output = ()>, T> futures_core::Stream for RowStream<F, T> {
+                                type Item = T;
+
+                                fn poll_next(
+                                    mut self: std::pin::Pin<&mut Self>,
+                                    cx: &mut std::task::Context<'_>,
+                                ) -> Poll<Option<T>> {
+                                    if self.done {
+                                        return Poll::Ready(None);
+                                    }
+                                    self.done = std::future::Future::poll(self.future.as_mut(), cx).is_ready();
+                                    match self.slot.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                                        Some(item) => Poll::Ready(Some(item)),
+                                        None if self.done => Poll::Ready(None),
+                                        None => Poll::Pending,
+                                    }
+                                }
+                            }
+
+                            // values are encoded now, so the stream only borrows `executor`
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let query = Self::stream_all_query(filter, tbname, #tenant_pass db_name);
+
+                            let slot = std::sync::Arc::new(std::sync::Mutex::new(None));
+                            let sender = slot.clone();
+                            let send = move |row: sqlx::Result<Self>| {
+                                *sender.lock().unwrap_or_else(|e| e.into_inner()) = Some(row);
+                            };
+                            let future = async move {
+                                let (sql, arguments) = match query {
+                                    Ok(query) => query,
+                                    Err(e) => return send(Err(e)),
+                                };
+                                let mut rows = sqlx::query_with(&sql, arguments)
+                                    .try_map(|row: sqlx::mysql::MySqlRow| Self::from_row(&row))
+                                    .fetch(executor);
+                                while let Some(row) =
+                                    std::future::poll_fn(|cx| futures_core::Stream::poll_next(rows.as_mut(), cx)).await
+                                {
+                                    send(row);
+                                    // pending once, so `RowStream` returns the row before the next one is fetched
+                                    // (and woken at once, the next poll resumes fetching)
+                                    let mut sent = false;
+                                    std::future::poll_fn(|cx| match std::mem::replace(&mut sent, true) {
+                                        true => Poll::Ready(()),
+                                        false => {
+                                            cx.waker().wake_by_ref();
+                                            Poll::Pending
+                                        }
+                                    })
+                                    .await;
+                                }
+                            };
+                            Box::pin(RowStream {
+                                future: Box::pin(future),
+                                slot,
+                                done: false,
+                            })
+                        }
+
+                        /// SQL of `stream_all` and its arguments (values of `filter`, then `tenant`)
+                        fn stream_all_query(
+                            filter: &#filtertype,
+                            tbname: &str,
+                            #tenant_arg
+                            db_name: &str,
+                        ) -> sqlx::Result<(String, sqlx::mysql::MySqlArguments)> {
+                            let sql = [
+                                "SELECT ", &Self::field_names().join(","), " FROM ", db_name, ".", tbname,
+                                &Self::scope_where(filter.to_sql(), #filter_with_deleted), ";"
+                            ].join("");
+
+                            let mut query = sqlx::query("");
+                            query = filter.bind(query);
+                            #tenant_bind_stream
+                            let arguments = sqlx::Execute::take_arguments(&mut query).map_err(sqlx::Error::Encode)?;
+                            Ok((sql, arguments.unwrap_or_default()))
+                        }
+                    }
+                });
+                let tenant_bind_count = tenant_bind.clone();
+                let tenant_bind_exists = tenant_bind.clone();
+                let idents_exists = own_fields.iter().map(|f| &f.ident);
+                let columns_exists = own_fields.iter().map(|f| &f.column);
+                let tenant_bind_update_where = tenant_bind.clone();
+                let tenant_bind_delete_where = tenant_bind.clone();
+
+                // `filter` types in `#filtermod`, only with `#[sqlx_binder(filter)]`: the module refers to the
+                // struct's scope by `super`, so the struct must not be declared inside a function
+                let filter_module = filter.then(|| {
+                    let module_doc = format!("`filter` types of [`{}`]", ident);
+                    quote! {
+                        #[doc = #module_doc]
+                        #vis mod #filtermod {
+                            #[allow(unused_imports)]
+                            use super::*;
+
+                            /// Typed `WHERE` conditions over the columns, for `select`, `count`, `update_where` and `delete_where`.<br>
+                            /// Conditions are joined by `AND` unless `or()` is called in between.
+                            #[derive(Debug, Clone)]
+                            pub struct Filter #impl_generics #where_clause {
+                                sql: String,
+                                values: Vec<super::#enumtype>,
+                                connector: &'static str,
+                                #with_deleted_field
+                            }
+
+                            impl #impl_generics Default for Filter #ty_generics #where_clause {
+                                fn default() -> Self {
+                                    Filter {
+                                        sql: String::new(),
+                                        values: Vec::new(),
+                                        connector: " AND ",
+                                        #with_deleted_default
+                                    }
+                                }
+                            }
+
+                            impl #impl_generics Filter #ty_generics #where_clause {
+                                pub fn new() -> Self {
+                                    Self::default()
+                                }
+
+                                /// Join the next condition with `AND` (the default)
+                                pub fn and(mut self) -> Self {
+                                    self.connector = " AND ";
+                                    self
+                                }
+
+                                /// Join the next condition with `OR`
+                                pub fn or(mut self) -> Self {
+                                    self.connector = " OR ";
+                                    self
+                                }
+
+                                /// Add every condition of `filter` in parentheses, as one condition
+                                pub fn group(self, filter: Self) -> Self {
+                                    if filter.is_empty() {
+                                        return self;
+                                    }
+                                    let condition = ["(", filter.to_sql(), ")"].concat();
+                                    self.push(&condition, filter.values)
+                                }
+
+                                #with_deleted_method
+
+                                pub fn is_empty(&self) -> bool {
+                                    self.sql.is_empty()
+                                }
+
+                                /// Conditions with `?` placeholders, without `WHERE`
+                                pub fn to_sql(&self) -> &str {
+                                    &self.sql
+                                }
+
+                                /// Bind every value of the conditions in order
+                                pub fn bind<'q>(
+                                    &self,
+                                    mut query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                                ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>
+                                where
+                                    super::#enumtype: 'q,
+                                {
+                                    for value in &self.values {
+                                        query = value.clone().bind(query);
+                                    }
+                                    query
+                                }
+
+                                fn push(mut self, condition: &str, values: impl IntoIterator<Item = super::#enumtype>) -> Self {
+                                    if !self.sql.is_empty() {
+                                        self.sql.push_str(self.connector);
+                                    }
+                                    self.sql.push_str(condition);
+                                    self.values.extend(values);
+                                    self.connector = " AND ";
+                                    self
+                                }
+
+                                /// Pick the column of the next condition, ex: `filter.col().age().gt(3)`
+                                pub fn col(self) -> FilterColumns #ty_generics {
+                                    FilterColumns { filter: self }
+                                }
+                            }
+
+                            /// Every column of `Filter`, one method per field
+                            pub struct FilterColumns #impl_generics #where_clause {
+                                pub(super) filter: Filter #ty_generics,
+                            }
+
+                            /// Column for `ORDER BY` of `select_page`
+                            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                            #[allow(non_camel_case_types, dead_code)]
+                            pub enum Column {
+                                #(#idents_column,)*
+                            }
+
+                            impl Column {
+                                pub fn as_str(&self) -> &'static str {
+                                    match self {
+                                        #(Column::#idents_column => #columns_column,)*
+                                    }
+                                }
+                            }
+
+                            /// Direction of `ORDER BY` of `select_page`
+                            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                            pub enum Order {
+                                Asc,
+                                Desc,
+                            }
+
+                            impl Order {
+                                pub(super) fn as_sql(&self) -> &'static str {
+                                    match self {
+                                        Order::Asc => " ASC",
+                                        Order::Desc => " DESC",
+                                    }
+                                }
+                            }
+
+                            /// Page of `select_page`
+                            #[derive(Debug, Clone)]
+                            pub enum Page #impl_generics #where_clause {
+                                /// `LIMIT limit OFFSET offset`
+                                Offset { limit: u64, offset: u64 },
+                                /// `limit` rows after `after` (the primary key of the last row of the previous page,
+                                /// `None` for the first page), for large tables where `OFFSET` is slow
+                                Keyset {
+                                    after: Option<super::#enumtype>,
+                                    limit: u64,
+                                },
+                            }
+
+                            /// A column of `Filter` waiting for its condition
+                            pub struct FilterColumn #column_impl_generics #where_clause {
+                                pub(super) filter: Filter #ty_generics,
+                                pub(super) column: &'static str,
+                                pub(super) value: fn(__V) -> super::#enumtype,
+                            }
+
+                            impl #column_impl_generics FilterColumn #column_ty_generics #where_clause {
+                                fn compare(self, operator: &str, value: __V) -> Filter #ty_generics {
+                                    let condition = [self.column, operator, "?"].concat();
+                                    self.filter.push(&condition, [(self.value)(value)])
+                                }
+
+                                /// `column=?`
+                                pub fn eq(self, value: __V) -> Filter #ty_generics {
+                                    self.compare("=", value)
+                                }
+
+                                /// `column<>?`
+                                pub fn ne(self, value: __V) -> Filter #ty_generics {
+                                    self.compare("<>", value)
+                                }
+
+                                /// `column>?`
+                                pub fn gt(self, value: __V) -> Filter #ty_generics {
+                                    self.compare(">", value)
+                                }
+
+                                /// `column>=?`
+                                pub fn ge(self, value: __V) -> Filter #ty_generics {
+                                    self.compare(">=", value)
+                                }
+
+                                /// `column<?`
+                                pub fn lt(self, value: __V) -> Filter #ty_generics {
+                                    self.compare("<", value)
+                                }
+
+                                /// `column<=?`
+                                pub fn le(self, value: __V) -> Filter #ty_generics {
+                                    self.compare("<=", value)
+                                }
+
+                                /// `column IS NULL`
+                                pub fn is_null(self) -> Filter #ty_generics {
+                                    let condition = [self.column, " IS NULL"].concat();
+                                    self.filter.push(&condition, [])
+                                }
+
+                                /// `column IS NOT NULL`
+                                pub fn is_not_null(self) -> Filter #ty_generics {
+                                    let condition = [self.column, " IS NOT NULL"].concat();
+                                    self.filter.push(&condition, [])
+                                }
+
+                                /// `column IN (?,?,..)`, an empty list matches no row
+                                pub fn in_list(self, values: impl IntoIterator<Item = __V>) -> Filter #ty_generics {
+                                    let values = values.into_iter().map(self.value).collect::<Vec<_>>();
+                                    if values.is_empty() {
+                                        return self.filter.push("1=0", []);
+                                    }
+                                    let condition = [self.column, " IN (", &vec!["?"; values.len()].join(","), ")"].concat();
+                                    self.filter.push(&condition, values)
+                                }
+                            }
+
+                            impl #impl_generics FilterColumn<#(#generic_args,)* ::std::string::String> #where_clause {
+                                /// `column LIKE ?`
+                                pub fn like(self, pattern: &str) -> Filter #ty_generics {
+                                    self.compare(" LIKE ", pattern.to_string())
+                                }
+                            }
+
+                            impl #impl_generics FilterColumn<#(#generic_args,)* Option<::std::string::String>> #where_clause {
+                                /// `column LIKE ?`
+                                pub fn like(self, pattern: &str) -> Filter #ty_generics {
+                                    self.compare(" LIKE ", Some(pattern.to_string()))
+                                }
+                            }
+                        }
+
+                        impl #impl_generics #columnstype #where_clause {
+                            #(#column_methods)*
+                        }
+                    }
+                });
+                // `#patchname` and the methods taking it are only generated with `#[sqlx_binder(patch)]` (or `patch_serde`)
+                let patch_methods = patch.then(|| {
+                    quote! {
+                        /// UPDATE only the columns of `patch` that are `Some`, `WHERE` is `primary_key` column with the struct's value.<br>
+                        /// `version` and every `guard` field are added to `WHERE` with the struct's values, like `update`,
+                        /// so with a `version` field `VersionConflict` is returned when no row matched and the field is increased on success.<br>
+                        /// `updated_at` columns are SET to `NOW()`, the struct's other fields are not changed (see `apply_patch`).<br>
+                        /// Nothing is executed (an empty result is returned) when every column of `patch` is `None`.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                        pub async fn update_patch(
+                            #update_self,
+                            primary_key: &str,
+                            patch: &#patchtype,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> #update_result {
+
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let keys = Self::patch_columns(patch);
+                            if keys.is_empty() {
+                                return Ok(MySqlQueryResult::default());
+                            }
+
+                            let sql = Self::update_patch_sql(primary_key, &keys, tbname, db_name)?;
+
+                            let mut query = sqlx::query(&sql);
+                            query = Self::bind_patch(patch, query);
+                            query = self.bind_column(query, primary_key);
+                            #version_bind
+                            #(#guard_bind_patch)*
+                            #tenant_bind
+                            #update_patch_execute
+                        }
+
+                        /// SQL of `update_patch` SETting `keys`, built once per table and arguments
+                        fn update_patch_sql(
+                            primary_key: &str,
+                            keys: &[&str],
+                            tbname: &str,
+                            db_name: &str,
+                        ) -> sqlx::Result<std::sync::Arc<str>> {
+                            Self::check_column(primary_key)?;
+                            static UPDATE_PATCH: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            let mut cache_key = vec!["update_patch", primary_key, tbname, db_name];
+                            cache_key.extend(keys);
+                            Ok(Self::cached_sql(&UPDATE_PATCH, &cache_key, || [
+                                "UPDATE ", db_name, ".", tbname, " SET ", &Self::patch_sets(keys),
+                                " WHERE ", primary_key, "=?", #version_where, #guard_where, #tenant_where, ";"
+                            ].join("")))
+                        }
+
+                        /// Columns of `patch` that are `Some`
+                        fn patch_columns(patch: &#patchtype) -> Vec<&'static str> {
+                            let mut keys = Vec::new();
+                            #(
+                                if patch.#idents_patch.is_some() {
+                                    keys.push(#columns_patch);
+                                }
+                            )*
+                            keys
+                        }
+
+                        /// `SET` of `patch_columns`, with `updated_at` and `version` expressions
+                        fn patch_sets(keys: &[&str]) -> String {
+                            let mut sets = keys.iter().map(|k| [k, "=?"].join("")).collect::<Vec<String>>();
+                            #(sets.push(#set_expressions_patch.to_string());)*
+                            sets.join(",")
+                        }
+
+                        fn bind_patch<'q>(
+                            patch: &'q #patchtype,
+                            mut query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                        ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+                            #(
+                                if let Some(value) = &patch.#idents_patch {
+                                    #binds_patch
+                                }
+                            )*
+                            query
+                        }
+
+                        /// Copy every `Some` column of `patch` to the struct's fields
+                        pub fn apply_patch(&mut self, patch: &#patchtype) {
+                            #(
+                                if let Some(value) = &patch.#idents_patch {
+                                    self.#members_patch = value.clone();
+                                }
+                            )*
+                        }
+                    }
+                });
+                // `PartialEq` of `#patchname` with `json` columns, comparing them by their serialized JSON
+                let patch_compare = has_json.then(|| {
+                    let mut generics = bounded.clone();
+                    let where_clause = generics.make_where_clause();
+                    let eqs = patch_fields
+                        .iter()
+                        .map(|f| {
+                            let (ident, ty) = (&f.ident, f.ty);
+                            if f.json {
+                                where_clause.predicates.push(syn::parse_quote!(#ty: serde::Serialize));
+                                quote! { json_eq(&self.#ident, &other.#ident) }
+                            } else {
+                                where_clause.predicates.push(syn::parse_quote!(#ty: PartialEq));
+                                quote! { self.#ident == other.#ident }
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    let eq = match eqs.is_empty() {
+                        true => quote! { true },
+                        false => quote! { #(#eqs)&&* },
+                    };
+                    let json_eq = patch_fields.iter().any(|f| f.json).then(|| {
+                        quote! {
+                            fn json_eq<__V: serde::Serialize>(l: &__V, r: &__V) -> bool {
+                                matches!((serde_json::to_string(l), serde_json::to_string(r)), (Ok(l), Ok(r)) if l == r)
+                            }
+                        }
+                    });
+                    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+                    quote! {
+                        impl #impl_generics PartialEq for #patchname #ty_generics #where_clause {
+                            fn eq(&self, other: &Self) -> bool {
+                                #json_eq
+                                #eq
+                            }
+                        }
+                    }
+                });
+                let patch_items = patch.then(|| {
+                    quote! {
+                        /// Every column that `update` may SET as an `Option`, for `update_patch` and `apply_patch`
+                        #patch_derive
+                        #patch_derive_serde
+                        pub struct #patchname #impl_generics #where_clause {
+                            #(
+                                #patch_field_serde
+                                pub #idents_patch: Option<#tys_patch>,
+                            )*
+                            #patch_phantom_field
+                        }
+
+                        #patch_deserialize_some
+
+                        impl #impl_generics Default for #patchtype #where_clause {
+                            fn default() -> Self {
+                                #patchname {
+                                    #(#idents_patch: None,)*
+                                    #patch_phantom_default
+                                }
+                            }
+                        }
+
+                        #patch_compare
+                    }
+                });
+                let update_where = patch.then(|| {
+                    quote! {
+                        /// UPDATE the columns of `patch` that are `Some` on every row matching `filter`.<br>
+                        /// An empty `filter` is refused with `sqlx::Error::InvalidArgument`,
+                        /// nothing is executed (an empty result is returned) when every column of `patch` is `None`.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                        pub async fn update_where(
+                            filter: &#filtertype,
+                            patch: &#patchtype,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
+                            if filter.is_empty() {
+                                return Err(sqlx::Error::InvalidArgument("update_where with an empty filter".to_string()));
+                            }
+                            let keys = Self::patch_columns(patch);
+                            if keys.is_empty() {
+                                return Ok(MySqlQueryResult::default());
+                            }
+
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let sql = [
+                                "UPDATE ", db_name, ".", tbname, " SET ", &Self::patch_sets(&keys),
+                                &Self::scope_where(filter.to_sql(), #filter_with_deleted), ";"
+                            ].join("");
+
+                            let mut query = sqlx::query(&sql);
+                            query = Self::bind_patch(patch, query);
+                            query = filter.bind(query);
+                            #tenant_bind_update_where
+                            query.execute(pool).await
+                        }
+                    }
+                });
+                let exists_by_doc = format!(
+                    "- key = `{}::{}(..)`",
+                    enumname,
+                    own_fields.first().map(|f| f.ident.to_string()).unwrap_or_default()
+                );
+                // soft-deleted rows are only looked up on request
+                let exists_by_with_deleted = soft_delete.map(|_| {
+                    quote! {
+                        /// Same as `exists_by`, but soft-deleted rows are found too
+                        pub async fn exists_by_with_deleted(
+                            key: #enumtype,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<bool> {
+                            Self::exists_by_with(key, true, custom_table_name, #tenant_pass pool, db_name).await
+                        }
+                    }
+                });
+                // methods taking a `Filter` (only with `#[sqlx_binder(filter)]`)
+                let filter_methods = filter.then(|| {
+                    let idents_after = own_fields.iter().map(|f| &f.ident);
+                    let columns_after = own_fields.iter().map(|f| &f.column);
+                    let filter_doc = format!(
+                        "Start a `{}::Filter`, ex: `{}::filter().col().{}().eq(..)`",
+                        filtermod,
+                        ident,
+                        own_fields.first().map(|f| f.ident.to_string()).unwrap_or_default()
+                    );
+                    let select_page_doc = format!(
+                        "`{0}::Page::Offset` pages are ordered by `order_by` then `primary_key` (when it is not in `order_by`),\n\
+                         `{0}::Page::Keyset` pages are ordered by `primary_key` only (`order_by` may only give its direction)",
+                        filtermod
+                    );
+                    quote! {
+                    #[doc = #filter_doc]
+                    pub fn filter() -> #filtertype {
+                        #filtermod::Filter::new()
+                    }
+
+                    /// SELECT every row matching `filter`.<br>
+                    /// Filter SQL is dynamic, so it is built on every call instead of going through the SQL cache.<br>
+                    /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                    pub async fn select(
+                        filter: &#filtertype,
+                        custom_table_name: Option<&str>,
+                        #tenant_arg
+                        pool: &Pool<MySql>,
+                        db_name: &str,
+                    ) -> sqlx::Result<Vec<Self>>
+                    where
+                        #fetch_bounds
+                    {
+                        let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                        let sql = [
+                            "SELECT ", &Self::field_names().join(","), " FROM ", db_name, ".", tbname,
+                            &Self::scope_where(filter.to_sql(), #filter_with_deleted), ";"
+                        ].join("");
+
+                        let mut query = sqlx::query(&sql);
+                        query = filter.bind(query);
+                        #tenant_bind_filter
+                        query.fetch_all(pool).await?.iter().map(|row| Self::from_row(row)).collect()
+                    }
+
+                    #stream_all
+
+                    /// COUNT rows matching `filter`.<br>
+                    /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                    pub async fn count(
+                        filter: &#filtertype,
+                        custom_table_name: Option<&str>,
+                        #tenant_arg
+                        pool: &Pool<MySql>,
+                        db_name: &str,
+                    ) -> sqlx::Result<i64> {
+                        let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                        let sql = [
+                            "SELECT COUNT(*) FROM ", db_name, ".", tbname,
+                            &Self::scope_where(filter.to_sql(), #filter_with_deleted), ";"
+                        ].join("");
+
+                        let mut query = sqlx::query(&sql);
+                        query = filter.bind(query);
+                        #tenant_bind_count
+                        sqlx::Row::try_get(&query.fetch_one(pool).await?, 0)
+                    }
+
+                    /// SELECT one page of rows matching `filter`, together with the total count of matching rows.<br>
+                    /// `primary_key` is the unique column that makes the order total:
+                    #[doc = #select_page_doc]
+                    /// and continue after a value of `primary_key`, anything else is refused with `sqlx::Error::InvalidArgument`.<br>
+                    /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                    #[allow(clippy::too_many_arguments)]
+                    pub async fn select_page(
+                        filter: &#filtertype,
+                        primary_key: #filtermod::Column,
+                        order_by: &[(#filtermod::Column, #filtermod::Order)],
+                        page: &#pagetype,
+                        custom_table_name: Option<&str>,
+                        #tenant_arg
+                        pool: &Pool<MySql>,
+                        db_name: &str,
+                    ) -> sqlx::Result<(Vec<Self>, i64)>
+                    where
+                        #fetch_bounds
+                    {
+                        let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                        let (sql, after, limit, offset) =
+                            Self::select_page_sql(filter, primary_key, order_by, page, tbname, db_name)?;
+                        let total = Self::count(filter, custom_table_name, #tenant_pass pool, db_name).await?;
+
+                        let mut query = sqlx::query(&sql);
+                        query = filter.bind(query);
+                        #tenant_bind_page
+                        if let Some(after) = after {
+                            query = after.clone().bind(query);
+                        }
+                        query = query.bind(limit).bind(offset);
+                        let rows = query.fetch_all(pool).await?.iter().map(|row| Self::from_row(row)).collect::<sqlx::Result<Vec<_>>>()?;
+                        Ok((rows, total))
+                    }
+
+                    /// SQL of `select_page` and the values bound after `filter`'s (`after`, `limit` and `offset`)
+                    fn select_page_sql<'p>(
+                        filter: &#filtertype,
+                        primary_key: #filtermod::Column,
+                        order_by: &[(#filtermod::Column, #filtermod::Order)],
+                        page: &'p #pagetype,
+                        tbname: &str,
+                        db_name: &str,
+                    ) -> sqlx::Result<(String, Option<&'p #enumtype>, u64, u64)> {
+                        let mut where_sql = Self::scope_where(filter.to_sql(), #filter_with_deleted);
+                        let primary_order = order_by
+                            .iter()
+                            .find(|(column, _)| *column == primary_key)
+                            .map(|(_, order)| *order);
+                        let (orders, limit, offset, after) = match page {
+                            #filtermod::Page::Offset { limit, offset } => {
+                                let mut orders = order_by
+                                    .iter()
+                                    .map(|(column, order)| [column.as_str(), order.as_sql()].concat())
+                                    .collect::<Vec<_>>();
+                                if primary_order.is_none() {
+                                    orders.push([primary_key.as_str(), " ASC"].concat());
+                                }
+                                (orders, *limit, *offset, None)
+                            }
+                            #filtermod::Page::Keyset { after, limit } => {
+                                if order_by.iter().any(|(column, _)| *column != primary_key) {
+                                    return Err(sqlx::Error::InvalidArgument(
+                                        "keyset page can only be ordered by the primary key".to_string(),
+                                    ));
+                                }
+                                let order = primary_order.unwrap_or(#filtermod::Order::Asc);
+                                if let Some(after) = after {
+                                    let after_column = match after {
+                                        #(#enumname::#idents_after(_) => #columns_after,)*
+                                        #(#enumname::#flatten_idents(_) => stringify!(#flatten_idents),)*
+                                        #phantom_key
+                                    };
+                                    if after_column != primary_key.as_str() {
+                                        return Err(sqlx::Error::InvalidArgument(format!(
+                                            "keyset page after '{}', expected the primary key '{}'",
+                                            after_column,
+                                            primary_key.as_str()
+                                        )));
+                                    }
+                                    let operator = match order {
+                                        #filtermod::Order::Asc => ">?",
+                                        #filtermod::Order::Desc => "<?",
+                                    };
+                                    let connector = if where_sql.is_empty() { " WHERE " } else { " AND " };
+                                    where_sql = [&where_sql, connector, primary_key.as_str(), operator].concat();
+                                }
+                                (vec![[primary_key.as_str(), order.as_sql()].concat()], *limit, 0, after.as_ref())
+                            }
+                        };
+                        let sql = [
+                            "SELECT ", &Self::field_names().join(","), " FROM ", db_name, ".", tbname,
+                            &where_sql, " ORDER BY ", &orders.join(","), " LIMIT ? OFFSET ?;"
+                        ].join("");
+                        Ok((sql, after, limit, offset))
+                    }
 
-    let output = match data {
-        syn::Data::Struct(s) => match s.fields {
-            syn::Fields::Named(FieldsNamed { named, .. }) => {
-                let mut named_mut = named.clone();
-                let idents_filtered = named.iter().filter(|f| {
-                    let attrs = attributes(&f.attrs);
-                    match attrs.first() {
-                        Some(attr) => !matches!(attr, FieldAttribute::Skip),
-                        None => true,
+                    #update_where
+
+                    /// DELETE every row matching `filter`.<br>
+                    /// An empty `filter` is refused with `sqlx::Error::InvalidArgument`.<br>
+                    /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)
+                    pub async fn delete_where(
+                        filter: &#filtertype,
+                        custom_table_name: Option<&str>,
+                        #tenant_arg
+                        pool: &Pool<MySql>,
+                        db_name: &str,
+                    ) -> sqlx::Result<MySqlQueryResult> {
+                        if filter.is_empty() {
+                            return Err(sqlx::Error::InvalidArgument("delete_where with an empty filter".to_string()));
+                        }
+
+                        let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                        let sql = [
+                            "DELETE FROM ", db_name, ".", tbname,
+                            &Self::scope_where(filter.to_sql(), #filter_with_deleted), ";"
+                        ].join("");
+
+                        let mut query = sqlx::query(&sql);
+                        query = filter.bind(query);
+                        #tenant_bind_delete_where
+                        query.execute(pool).await
+                    }
+                    }
+                });
+
+                // `update_strict`/`delete_strict` are only generated with `#[sqlx_binder(strict)]`
+                let strict_methods = strict.then(|| {
+                    let mut update_strict_doc = format!(
+                        " `update`, but exactly one row must be affected, otherwise `{0}::NotFound` (no row) or `{0}::TooManyRows` (more than one row).",
+                        errname
+                    );
+                    if version.is_some() {
+                        update_strict_doc.push_str(&format!(
+                            "<br>\n With the `version` field no matching row is `{}::VersionConflict` instead of `NotFound`.",
+                            errname
+                        ));
+                    }
+                    let delete_strict_doc = format!(
+                        " `delete`, but exactly one row must be affected, otherwise `{0}::NotFound` (no row) or `{0}::TooManyRows` (more than one row).",
+                        errname
+                    );
+                    quote! {
+                        #[doc = #update_strict_doc]
+                        ///
+                        /// The statement runs in a transaction that is only committed when exactly one row is affected,
+                        /// so on error nothing is changed (and `version` is not increased).<br>
+                        /// sqlx connects with `CLIENT_FOUND_ROWS`, so a matched row counts even when none of its values changed,
+                        /// "not modified" is never reported as an error.
+                        #[allow(clippy::too_many_arguments)]
+                        pub async fn update_strict(
+                            #update_self,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            extra_column: &str,
+                            extra_values: &[&str],
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> Result<MySqlQueryResult, #errname> {
+                            #version_save
+                            let mut tx = pool.begin().await?;
+                            let result = self.update_with_actor(None, primary_key, custom_table_name, extra_column, extra_values, #tenant_pass &mut *tx, db_name).await?;
+                            let outcome = Self::commit_one_row(tx, result).await;
+                            #version_restore
+                            outcome
+                        }
+
+                        #[doc = #delete_strict_doc]
+                        ///
+                        /// The statement runs in a transaction that is rolled back unless exactly one row is affected.
+                        pub async fn delete_strict(
+                            &self,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> Result<MySqlQueryResult, #errname> {
+                            let mut tx = pool.begin().await?;
+                            let result = self.delete_with(primary_key, custom_table_name, #tenant_pass &mut *tx, db_name).await?;
+                            Self::commit_one_row(tx, result).await
+                        }
+
+                        async fn commit_one_row(
+                            tx: sqlx::Transaction<'static, MySql>,
+                            result: MySqlQueryResult,
+                        ) -> Result<MySqlQueryResult, #errname> {
+                            match Self::__expect_one_row(result.rows_affected()) {
+                                Ok(()) => {
+                                    tx.commit().await?;
+                                    Ok(result)
+                                }
+                                Err(e) => {
+                                    tx.rollback().await?;
+                                    Err(e)
+                                }
+                            }
+                        }
+
+                        #[doc(hidden)]
+                        pub fn __expect_one_row(rows_affected: u64) -> Result<(), #errname> {
+                            match rows_affected {
+                                1 => Ok(()),
+                                0 => Err(#errname::NotFound),
+                                n => Err(#errname::TooManyRows(n)),
+                            }
+                        }
+                    }
+                });
+
+                let version_conflict = version.map(|_| {
+                    quote! {
+                        /// `update` matched no row with the current `version`,
+                        /// the row was changed (or removed) since it was read.
+                        VersionConflict,
+                    }
+                });
+                let version_conflict_display = version.map(|_| {
+                    quote! {
+                        #errname::VersionConflict => {
+                            write!(f, "version conflict on '{}'", stringify!(#ident))
+                        }
                     }
                 });
-                let idents_getfield = named_mut.iter_mut().filter_map(|f| {
-                    let attrs = attributes(&f.attrs);
-                    match attrs.first() {
-                        Some(FieldAttribute::Skip) => None,
-                        Some(FieldAttribute::Rename(val)) => {
-                            Some(f.ident.as_ref().map(|id| Ident::new(val, id.span())))
+                let strict_variants = strict.then(|| {
+                    quote! {
+                        /// `update_strict`/`delete_strict` matched no row.
+                        NotFound,
+                        /// `update_strict`/`delete_strict` matched more than one row (the statement is rolled back).
+                        TooManyRows(u64),
+                    }
+                });
+                let strict_display = strict.then(|| {
+                    quote! {
+                        #errname::NotFound => write!(f, "no row of '{}' found", stringify!(#ident)),
+                        #errname::TooManyRows(n) => {
+                            write!(f, "{} rows of '{}' affected, expected 1", n, stringify!(#ident))
                         }
-                        _ => Some(f.ident.clone()),
                     }
                 });
+                // `#errname` is only generated when something can return it: a `version` field or `strict`
+                let binder_error = (version.is_some() || strict).then(|| quote! {
+                    #[derive(Debug)]
+                    pub enum #errname {
+                        Sqlx(sqlx::Error),
+                        #version_conflict
+                        #strict_variants
+                    }
 
-                let idents_enum = idents_filtered.clone().map(|f| &f.ident);
-                let tys_enum = idents_filtered.clone().map(|f| &f.ty);
+                    impl std::fmt::Display for #errname {
+                        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                            match self {
+                                #errname::Sqlx(e) => write!(f, "{}", e),
+                                #version_conflict_display
+                                #strict_display
+                            }
+                        }
+                    }
 
-                let idents_getenum = idents_enum.clone();
-                let idents_getenums = idents_enum.clone();
-                let idents_bind = idents_enum.clone();
+                    impl std::error::Error for #errname {
+                        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                            match self {
+                                #errname::Sqlx(e) => Some(e),
+                                _ => None,
+                            }
+                        }
+                    }
 
-                let enumname = format_ident!("{}{}", ident, "FieldEnum");
+                    impl From<sqlx::Error> for #errname {
+                        fn from(e: sqlx::Error) -> Self {
+                            #errname::Sqlx(e)
+                        }
+                    }
+                });
 
                 quote! {
 
-                    impl #ident {
+                    impl #impl_generics #ident #ty_generics #where_clause {
+
+                        #field_names_const
+
+                        /// Default table name, struct name in snake_case, the same as `get_struct_name_snake`
+                        pub const TABLE_NAME: &'static str = #table_name;
+
+                        /// Number of columns
+                        pub const FIELD_COUNT: usize = #field_count #(+ <#flatten_tys>::FIELD_COUNT)*;
+
+                        /// Column names, the same as `FIELD_NAMES`.<br>
+                        /// A struct with `flatten` fields has no `FIELD_NAMES`, this is the only way to get its column names.
+                        pub fn field_names() -> &'static [&'static str] {
+                            #field_names
+                        }
 
-                        pub fn get_enum(&self, field_string: &str) -> Result<#enumname, String> {
+                        pub fn get_enum(&self, field_string: &str) -> Result<#enumtype, String> {
                             match field_string {
                                 #(stringify!(#idents_getenum) => {
-                                    Ok(#enumname::#idents_getenum(self.#idents_getenum.clone()))
+                                    Ok(#enumname::#idents_getenum(self.#members.clone()))
                                 }),*
-                                _ => Err(format!("invalid field name to getenum '{}'", field_string)),
+                                _ => {
+                                    #(
+                                        if let Some(Ok(value)) = field_string
+                                            .strip_prefix(#flatten_prefixes)
+                                            .map(|name| self.#flatten_members.get_enum(name))
+                                        {
+                                            return Ok(#enumname::#flatten_idents(value));
+                                        }
+                                    )*
+                                    Err(format!("invalid field name to getenum '{}'", field_string))
+                                }
+                            }
+                        }
+
+                        #get_ref
+
+                        /// SQL text of generated statements is built once per `key`
+                        /// (statement kind and every argument that changes the text) and shared afterward,
+                        /// so sqlx's statement cache always gets the same text.<br>
+                        /// `statement` keeps the first key of one statement (the default table of one database, usually),
+                        /// which is compared without locking nor hashing, so the hot path has no string building.
+                        /// Other keys are looked up under a read lock, and at most 1024 texts are kept per struct
+                        /// (texts of further keys are built on every call instead of growing the cache).
+                        fn cached_sql(
+                            statement: &'static std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)>,
+                            key: &[&str],
+                            build: impl FnOnce() -> String,
+                        ) -> std::sync::Arc<str> {
+                            use std::hash::{Hash, Hasher};
+
+                            const CACHE_CAPACITY: usize = 1024;
+                            type Cache = std::collections::HashMap<u64, Vec<(Vec<String>, std::sync::Arc<str>)>>;
+                            static CACHE: std::sync::OnceLock<std::sync::RwLock<Cache>> = std::sync::OnceLock::new();
+
+                            match statement.get() {
+                                Some((first, sql)) if first.iter().map(String::as_str).eq(key.iter().copied()) => {
+                                    return sql.clone();
+                                }
+                                Some(_) => {}
+                                None => {
+                                    let sql: std::sync::Arc<str> = build().into();
+                                    let _ = statement.set((key.iter().map(|k| k.to_string()).collect(), sql.clone()));
+                                    return sql;
+                                }
+                            }
+
+                            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                            key.hash(&mut hasher);
+                            let hash = hasher.finish();
+                            let find = |cache: &Cache| {
+                                cache
+                                    .get(&hash)?
+                                    .iter()
+                                    .find(|(k, _)| k.iter().eq(key.iter().copied()))
+                                    .map(|(_, sql)| sql.clone())
+                            };
+
+                            let lock = CACHE.get_or_init(Default::default);
+                            if let Some(sql) = find(&lock.read().unwrap_or_else(|e| e.into_inner())) {
+                                return sql;
+                            }
+                            let sql: std::sync::Arc<str> = build().into();
+                            let mut cache = lock.write().unwrap_or_else(|e| e.into_inner());
+                            if let Some(sql) = find(&cache) {
+                                return sql;
                             }
+                            if cache.len() < CACHE_CAPACITY {
+                                cache
+                                    .entry(hash)
+                                    .or_default()
+                                    .push((key.iter().map(|k| k.to_string()).collect(), sql.clone()));
+                            }
+                            sql
                         }
 
                         /// return UpperCamelCase
@@ -60,37 +2132,46 @@ pub fn derive(input: TokenStream) -> TokenStream {
 
                         /// return snake_case
                         pub fn get_struct_name_snake(&self) -> String {
+                            Self::TABLE_NAME.to_string()
+                        }
 
-                            let text = self.get_struct_name();
+                        pub fn get_field_names(&self) -> Vec<&'static str> {
+                            Self::field_names().to_vec()
+                        }
 
-                            // The first character is never prepended with an underscore, so skip it even if it is an
-                            // uppercase ASCII character.
-                            let underscore_count = text.chars().skip(1).filter(|&c| c.is_ascii_uppercase()).count();
-                            let mut result = String::with_capacity(text.len() + underscore_count);
+                        pub fn get_field_enums(&self) -> Vec<#enumtype> {
+                            #get_field_enums
+                        }
 
-                            for (i, c) in text.chars().enumerate() {
-                                if c.is_ascii_uppercase() {
-                                    if i != 0 {
-                                        result.push('_');
-                                    }
-                                    result.push(c.to_ascii_lowercase());
-                                } else {
-                                    result.push(c);
-                                }
-                            }
+                        #get_field_refs
 
-                            result
+                        /// Decode a row selected with `field_names` columns, skipped fields are `Default::default()`
+                        pub fn from_row<'r>(row: &'r sqlx::mysql::MySqlRow) -> sqlx::Result<Self>
+                        where
+                            #(#decode_bounds)*
+                            #((#skipped_tys, std::marker::PhantomData<&'r ()>): Default,)*
+                        {
+                            Ok(Self {
+                                #(#decode_fields,)*
+                                #(#skipped_members: <(#skipped_tys, std::marker::PhantomData<&'r ()>)>::default().0,)*
+                            })
                         }
 
-                        pub fn get_field_names(&self) -> Vec<&'static str> {
-                            vec![#(stringify!(#idents_getfield)),*]
+                        /// Same as `from_row`, but every column name starts with `prefix`
+                        pub fn from_row_with_prefix<'r>(row: &'r sqlx::mysql::MySqlRow, prefix: &str) -> sqlx::Result<Self>
+                        where
+                            #(#decode_bounds)*
+                            #((#skipped_tys, std::marker::PhantomData<&'r ()>): Default,)*
+                        {
+                            Ok(Self {
+                                #(#decode_prefixed_fields,)*
+                                #(#skipped_members: <(#skipped_tys, std::marker::PhantomData<&'r ()>)>::default().0,)*
+                            })
                         }
 
-                        pub fn get_field_enums(&self) -> Vec<#enumname> {
-                            vec![#(#enumname::#idents_getenums(self.#idents_getenums.clone())),*]
-                        }
+                        #map_decode_error
 
-                        /// If `primary_key` is Some, will skip `primary_key` column.<br> 
+                        /// If `primary_key` is Some, will skip `primary_key` column.<br>
                         /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`).<br>
                         /// - custom_table_name = `Some("some_table_name")`<br>
                         /// `extra_column` and `extra_statement` MUST have the same amount and start with `,` (or "" for empty).<br>
@@ -106,148 +2187,402 @@ pub fn derive(input: TokenStream) -> TokenStream {
                             extra_column: &str,
                             extra_statement: &str,
                             extra_values: &[&str],
+                            #tenant_arg
                             pool: &Pool<MySql>,
                             db_name: &str,
                         ) -> sqlx::Result<MySqlQueryResult> {
+                            self.insert_with_actor(None, primary_key, custom_table_name, extra_column, extra_statement, extra_values, #tenant_pass pool, db_name).await
+                        }
 
-                            let tbname = custom_table_name.map(|s| s.to_string()).unwrap_or(self.get_struct_name_snake());
-                            let mut keys = self.get_field_names();
-                            let mut params = self.get_field_enums();
+                        #insert_as
 
-                            if let Some(pk) = primary_key {
-                                let position = keys.iter().position(|k| *k == pk)
-                                    .ok_or_else(|| sqlx::Error::ColumnNotFound(pk.to_string()))?;
-                                let _removed_keys = keys.swap_remove(position);
-                                let _removed_param = params.swap_remove(position);
-                            }
+                        #[allow(clippy::too_many_arguments)]
+                        async fn insert_with_actor(
+                            &self,
+                            actor: Option<&str>,
+                            primary_key: Option<&str>,
+                            custom_table_name: Option<&str>,
+                            extra_column: &str,
+                            extra_statement: &str,
+                            extra_values: &[&str],
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
 
-                            let sql = [
-                                "INSERT INTO ", db_name, ".", &tbname, " (",
-                                    &keys.join(","), extra_column,
-                                ") VALUE (",
-                                    &vec!["?"; keys.len()].join(","), extra_statement,
-                                ");"
-                            ].join("");
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let sql = self.insert_sql(actor.is_some(), primary_key, tbname, db_name, extra_column, extra_statement)?;
 
                             let mut query = sqlx::query(&sql);
-                            for param in params {
-                                query = param.bind(query);
+                            let mut columns = Self::field_names().iter();
+                            query = self.__bind_columns(query, &mut || {
+                                columns.next().is_some_and(|column| self.insert_binds(column, actor.is_some(), primary_key))
+                            });
+                            if let Some(actor) = actor {
+                                #(#insert_audit_bind)*
                             }
+                            #tenant_bind
                             for extra_value in extra_values {
                                 query = query.bind(extra_value);
                             }
                             query.execute(pool).await
                         }
 
-                        /// `primary_key` using for `WHERE` in sql.<br> 
+                        /// SQL of `insert` (of `insert_as` with `actor`), built once per table and arguments
+                        fn insert_sql(
+                            &self,
+                            actor: bool,
+                            primary_key: Option<&str>,
+                            tbname: &str,
+                            db_name: &str,
+                            extra_column: &str,
+                            extra_statement: &str,
+                        ) -> sqlx::Result<std::sync::Arc<str>> {
+                            if let Some(primary_key) = primary_key {
+                                Self::check_column(primary_key)?;
+                            }
+                            static INSERT: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            static INSERT_AS: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            let statement = if actor { &INSERT_AS } else { &INSERT };
+                            let key = [
+                                "insert", if actor { "actor" } else { "" }, primary_key.unwrap_or(""),
+                                tbname, db_name, extra_column, extra_statement,
+                                #(#insert_none_key,)*
+                            ];
+                            Ok(Self::cached_sql(statement, &key, || {
+                                let mut keys = Self::field_names()
+                                    .iter()
+                                    .copied()
+                                    .filter(|column| self.insert_binds(column, actor, primary_key))
+                                    .collect::<Vec<_>>();
+                                let mut values = vec!["?"; keys.len()];
+                                #(
+                                    keys.push(#insert_now);
+                                    values.push("NOW()");
+                                )*
+                                if actor {
+                                    #(
+                                        keys.push(#insert_audit);
+                                        values.push("?");
+                                    )*
+                                }
+                                #tenant_insert
+
+                                [
+                                    "INSERT INTO ", db_name, ".", tbname, " (",
+                                        &keys.join(","), extra_column,
+                                    ") VALUE (",
+                                        &values.join(","), extra_statement,
+                                    ");"
+                                ].join("")
+                            }))
+                        }
+
+                        /// Whether `insert` binds `column` (of `field_names`) from the struct's value
+                        fn insert_binds(&self, column: &str, #insert_actor_param: bool, primary_key: Option<&str>) -> bool {
+                            #(#insert_binds)&&*
+                        }
+
+                        /// `primary_key` using for `WHERE` in sql.<br>
+                        /// Every `guard` field is added to `WHERE` as `AND column=?` (and never SET).<br>
                         /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)<br>
                         /// - custom_table_name = `Some("some_table_name")`<br>
                         /// `extra_column` MUST start with `,` (or "" for empty `extra_column`).<br>
                         /// `extra_values` can be any type (MUST convert to `String` type) and have the same amount as `?` in `extra_column`.<br>
                         /// - extra_column = `,update_user=?,update_datetime=now(),version=1`<br>
                         /// - extra_values = `&["username"]`
+                        #[allow(clippy::too_many_arguments)]
                         pub async fn update(
-                            &self,
+                            #update_self,
                             primary_key: &str,
                             custom_table_name: Option<&str>,
                             extra_column: &str,
                             extra_values: &[&str],
+                            #tenant_arg
                             pool: &Pool<MySql>,
                             db_name: &str,
-                        ) -> sqlx::Result<MySqlQueryResult> {
+                        ) -> #update_result {
+                            self.update_with_actor(None, primary_key, custom_table_name, extra_column, extra_values, #tenant_pass pool, db_name).await
+                        }
 
-                            let tbname = custom_table_name.map(|s| s.to_string()).unwrap_or(self.get_struct_name_snake());
-                            let mut keys = self.get_field_names();
-                            let mut params = self.get_field_enums();
+                        #update_as
 
-                            let position = keys.iter().position(|k| *k == primary_key)
-                                .ok_or_else(|| sqlx::Error::ColumnNotFound(primary_key.to_string()))?;
-                            let removed_keys = keys.swap_remove(position);
-                            let removed_param = params.swap_remove(position);
+                        #[allow(clippy::too_many_arguments)]
+                        async fn update_with_actor<'c, E: sqlx::Executor<'c, Database = MySql>>(
+                            #update_self,
+                            actor: Option<&str>,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            extra_column: &str,
+                            extra_values: &[&str],
+                            #tenant_arg
+                            executor: E,
+                            db_name: &str,
+                        ) -> #update_result {
 
-                            let sql = [
-                                "UPDATE ", db_name, ".", &tbname, " SET ",
-                                &keys.iter().map(|k| [k, "=?"].join("")).collect::<Vec<String>>().join(","), extra_column,
-                                " WHERE ", removed_keys, "=?;"
-                            ].join("");
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let sql = Self::update_sql(actor.is_some(), primary_key, tbname, db_name, extra_column)?;
 
                             let mut query = sqlx::query(&sql);
-                            for param in params {
-                                query = param.bind(query);
+                            let mut columns = Self::field_names().iter();
+                            query = self.__bind_columns(query, &mut || {
+                                columns.next().is_some_and(|column| Self::update_binds(column, actor.is_some(), primary_key))
+                            });
+                            if let Some(actor) = actor {
+                                #(#update_audit_bind)*
                             }
                             for extra_value in extra_values {
                                 query = query.bind(extra_value);
                             }
-                            query = removed_param.bind(query);
-                            query.execute(pool).await
+                            query = self.bind_column(query, primary_key);
+                            #version_bind
+                            #(#guard_bind)*
+                            #tenant_bind
+                            #update_execute
+                        }
+
+                        /// SQL of `update` (of `update_as` with `actor`), built once per table and arguments
+                        fn update_sql(
+                            actor: bool,
+                            primary_key: &str,
+                            tbname: &str,
+                            db_name: &str,
+                            extra_column: &str,
+                        ) -> sqlx::Result<std::sync::Arc<str>> {
+                            Self::check_column(primary_key)?;
+                            static UPDATE: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            static UPDATE_AS: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            let statement = if actor { &UPDATE_AS } else { &UPDATE };
+                            let key = [
+                                "update", if actor { "actor" } else { "" }, primary_key,
+                                tbname, db_name, extra_column,
+                            ];
+                            Ok(Self::cached_sql(statement, &key, || {
+                                let mut sets = Self::field_names()
+                                    .iter()
+                                    .filter(|column| Self::update_binds(column, actor, primary_key))
+                                    .map(|column| [column, "=?"].concat())
+                                    .collect::<Vec<String>>();
+                                #(sets.push(#set_expressions.to_string());)*
+                                if actor {
+                                    #(sets.push(#update_audit.to_string());)*
+                                }
+
+                                [
+                                    "UPDATE ", db_name, ".", tbname, " SET ",
+                                    &sets.join(","), extra_column,
+                                    " WHERE ", primary_key, "=?", #version_where, #guard_where, #tenant_where, ";"
+                                ].join("")
+                            }))
+                        }
+
+                        /// Whether `update` SETs `column` (of `field_names`) from the struct's value
+                        fn update_binds(column: &str, #update_actor_param: bool, primary_key: &str) -> bool {
+                            #(#update_binds)&&*
                         }
 
-                        /// `primary_key` using for `WHERE` in sql.<br> 
+                        /// `primary_key` using for `WHERE` in sql, together with `AND column=?` for every `guard` field.<br>
                         /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)<br>
-                        /// - custom_table_name = `Some("some_table_name")`<br>
-                        /// `extra_column` MUST start with `,` (or "" for empty `extra_column`).<br>
-                        /// `extra_values` can be any type (MUST convert to `String` type) and have the same amount as `?` in `extra_column`.<br>
-                        /// - extra_column = `,update_user=?,update_datetime=now(),version=1`<br>
-                        /// - extra_values = `&["username"]`
-                        /// - create_user for `create_user=?`
-                        pub async fn update_by_creator(
+                        /// - custom_table_name = `Some("some_table_name")`
+                        pub async fn delete(
                             &self,
                             primary_key: &str,
                             custom_table_name: Option<&str>,
-                            extra_column: &str,
-                            extra_values: &[&str],
-                            create_user: &str,
+                            #tenant_arg
                             pool: &Pool<MySql>,
                             db_name: &str,
                         ) -> sqlx::Result<MySqlQueryResult> {
+                            self.delete_with(primary_key, custom_table_name, #tenant_pass pool, db_name).await
+                        }
 
-                            let tbname = custom_table_name.map(|s| s.to_string()).unwrap_or(self.get_struct_name_snake());
-                            let mut keys = self.get_field_names();
-                            let mut params = self.get_field_enums();
-
-                            let position = keys.iter().position(|k| *k == primary_key)
-                                .ok_or_else(|| sqlx::Error::ColumnNotFound(primary_key.to_string()))?;
-                            let removed_keys = keys.swap_remove(position);
-                            let removed_param = params.swap_remove(position);
+                        async fn delete_with<'c, E: sqlx::Executor<'c, Database = MySql>>(
+                            &self,
+                            primary_key: &str,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            executor: E,
+                            db_name: &str,
+                        ) -> sqlx::Result<MySqlQueryResult> {
 
-                            let sql = [
-                                "UPDATE ", db_name, ".", &tbname, " SET ",
-                                &keys.iter().map(|k| [k, "=?"].join("")).collect::<Vec<String>>().join(","), extra_column,
-                                " WHERE ", removed_keys, "=? AND create_user=?;"
-                            ].join("");
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let sql = Self::delete_sql(primary_key, tbname, db_name)?;
 
                             let mut query = sqlx::query(&sql);
-                            for param in params {
-                                query = param.bind(query);
+                            query = self.bind_column(query, primary_key);
+                            #(#guard_bind_delete)*
+                            #tenant_bind
+                            query.execute(executor).await
+                        }
+
+                        /// SQL of `delete`, built once per table and arguments
+                        fn delete_sql(primary_key: &str, tbname: &str, db_name: &str) -> sqlx::Result<std::sync::Arc<str>> {
+                            Self::check_column(primary_key)?;
+                            static DELETE: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            Ok(Self::cached_sql(&DELETE, &["delete", primary_key, tbname, db_name], || [
+                                "DELETE FROM ", db_name, ".", tbname,
+                                " WHERE ", primary_key, "=?", #guard_where, #tenant_where, ";"
+                            ].join("")))
+                        }
+
+                        /// Whether `insert` never binds `column` (of `field_names`, unquoted), for structs flattening this one
+                        #[doc(hidden)]
+                        pub fn __insert_skips(column: &str) -> bool {
+                            #insert_skips
+                        }
+
+                        /// Whether `update` never SETs `column` (of `field_names`, unquoted), for structs flattening this one
+                        #[doc(hidden)]
+                        pub fn __update_skips(column: &str) -> bool {
+                            #update_skips
+                        }
+
+                        /// Whether the struct has attributes that a struct flattening this one can not apply
+                        #[doc(hidden)]
+                        pub const __FLATTEN_UNSUPPORTED: bool = #flatten_unsupported #(#flatten_unsupported_nested)*;
+
+                        /// `sqlx::Error::ColumnNotFound` unless `column` is one of `field_names`
+                        fn check_column(column: &str) -> sqlx::Result<()> {
+                            match Self::field_names().contains(&column) {
+                                true => Ok(()),
+                                false => Err(sqlx::Error::ColumnNotFound(column.to_string())),
                             }
-                            for extra_value in extra_values {
-                                query = query.bind(extra_value);
+                        }
+
+                        /// Bind the value of every column of `field_names` that `keep` returns true for
+                        /// (`keep` is called once per column, in order), without cloning the values
+                        #[doc(hidden)]
+                        pub fn __bind_columns<'q>(
+                            &'q self,
+                            mut query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                            keep: &mut dyn FnMut() -> bool,
+                        ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+                            #(#binds_columns)*
+                            query
+                        }
+
+                        /// Bind the value of `column` (of `field_names`)
+                        fn bind_column<'q>(
+                            &'q self,
+                            query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                            column: &str,
+                        ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+                            let mut columns = Self::field_names().iter();
+                            self.__bind_columns(query, &mut || columns.next().is_some_and(|c| *c == column))
+                        }
+
+                        #strict_methods
+
+                        #patch_methods
+
+                        /// ` WHERE ...` of `condition` (without `WHERE`, "" for none), together with `tenant` and `soft_delete` conditions
+                        fn scope_where(condition: &str, #with_deleted_param: bool) -> String {
+                            let mut conditions: Vec<String> = Vec::new();
+                            if !condition.is_empty() {
+                                conditions.push(["(", condition, ")"].concat());
+                            }
+                            #tenant_condition
+                            #soft_delete_condition
+                            if conditions.is_empty() {
+                                String::new()
+                            } else {
+                                [" WHERE ", &conditions.join(" AND ")].concat()
                             }
-                            query = removed_param.bind(query);
-                            query.bind(create_user).execute(pool).await
                         }
+
+                        #filter_methods
+
+                        /// Whether a row with `key` exists (`SELECT EXISTS`), `tenant` and `soft_delete` conditions are added.<br>
+                        /// If `custom_table_name` is None, will use struct name as table_name (automatically convert `PascalCase` to `snake_case`)<br>
+                        #[doc = #exists_by_doc]
+                        pub async fn exists_by(
+                            key: #enumtype,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<bool> {
+                            Self::exists_by_with(key, false, custom_table_name, #tenant_pass pool, db_name).await
+                        }
+
+                        #exists_by_with_deleted
+
+                        async fn exists_by_with(
+                            key: #enumtype,
+                            with_deleted: bool,
+                            custom_table_name: Option<&str>,
+                            #tenant_arg
+                            pool: &Pool<MySql>,
+                            db_name: &str,
+                        ) -> sqlx::Result<bool> {
+                            let tbname = custom_table_name.unwrap_or(Self::TABLE_NAME);
+                            let primary_key = match &key {
+                                #(#enumname::#idents_exists(_) => #columns_exists,)*
+                                #(#enumname::#flatten_idents(_) => {
+                                    return Err(sqlx::Error::ColumnNotFound(stringify!(#flatten_idents).to_string()));
+                                })*
+                                #phantom_key
+                            };
+
+                            let sql = Self::exists_by_sql(primary_key, with_deleted, tbname, db_name);
+
+                            let mut query = sqlx::query(&sql);
+                            query = key.bind(query);
+                            #tenant_bind_exists
+                            let exists: i64 = sqlx::Row::try_get(&query.fetch_one(pool).await?, 0)?;
+                            Ok(exists != 0)
+                        }
+
+                        /// SQL of `exists_by` (of `exists_by_with_deleted` with `with_deleted`), built once per table and arguments
+                        fn exists_by_sql(primary_key: &str, with_deleted: bool, tbname: &str, db_name: &str) -> std::sync::Arc<str> {
+                            static EXISTS_BY: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            static EXISTS_BY_WITH_DELETED: std::sync::OnceLock<(Vec<String>, std::sync::Arc<str>)> = std::sync::OnceLock::new();
+                            let statement = if with_deleted { &EXISTS_BY_WITH_DELETED } else { &EXISTS_BY };
+                            let key = ["exists_by", if with_deleted { "with_deleted" } else { "" }, primary_key, tbname, db_name];
+                            Self::cached_sql(statement, &key, || [
+                                "SELECT EXISTS(SELECT 1 FROM ", db_name, ".", tbname,
+                                &Self::scope_where(&[primary_key, "=?"].concat(), with_deleted), ");"
+                            ].join(""))
+                        }
+
+                        #soft_delete_methods
                     }
 
-                    #[derive(Debug, PartialEq, PartialOrd, Clone)]
+                    #filter_module
+
+                    #patch_items
+
+                    #flatten_check
+
+                    #binder_error
+
+                    #enum_derive
                     #[allow(non_camel_case_types)]
-                    pub enum #enumname{
-                        #(#idents_enum(#tys_enum)),*
+                    pub enum #enumname #impl_generics #where_clause {
+                        #(#idents_enum(#tys_enum),)*
+                        #phantom
                     }
 
-                    impl #enumname {
-                        pub fn bind(
+                    impl #impl_generics #enumtype #where_clause {
+                        pub fn bind<'q>(
                             self,
-                            query: sqlx::query::Query<'_, sqlx::MySql, sqlx::mysql::MySqlArguments>,
-                        ) -> sqlx::query::Query<'_, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+                            query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+                        ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>
+                        where
+                            Self: 'q,
+                        {
                             match self {
-                                #(#enumname::#idents_bind(p) => query.bind(p)),*
+                                #(#enumname::#idents_bind(p) => #binds_enum,)*
+                                #phantom_bind
                             }
                         }
                     }
+
+                    #enum_compare
+
+                    #field_ref_items
                 }
             }
-            syn::Fields::Unnamed(_) => panic!("Only NamedFields is supported"),
-            syn::Fields::Unit => panic!("Only NamedFields is supported"),
+            syn::Fields::Unit => panic!("Unit struct is not supported"),
         },
         syn::Data::Enum(_) => panic!("Enum is not supported. Only struct is supported"),
         syn::Data::Union(_) => panic!("Union is not supported. Only struct is supported"),
@@ -258,9 +2593,34 @@ pub fn derive(input: TokenStream) -> TokenStream {
 fn attributes(attrs: &[Attribute]) -> Vec<FieldAttribute> {
     let mut res = Vec::new();
 
-    for attr in attrs {
+    for list in binder_lists(attrs) {
+        match list.parse_args::<FieldAttributes>() {
+            Ok(items) => res.extend(items.attrs),
+            Err(e) => panic!("Error parsing field attributes: {}", e),
+        }
+    }
+
+    res
+}
+
+fn struct_attributes(attrs: &[Attribute]) -> Vec<StructAttribute> {
+    let mut res = Vec::new();
+
+    for list in binder_lists(attrs) {
+        match list.parse_args::<StructAttributes>() {
+            Ok(items) => res.extend(items.attrs),
+            Err(e) => panic!("Error parsing struct attributes: {}", e),
+        }
+    }
+
+    res
+}
+
+/// `#[sqlx_binder(...)]` lists of an item
+fn binder_lists(attrs: &[Attribute]) -> impl Iterator<Item = &MetaList> {
+    attrs.iter().filter_map(|attr| {
         if attr.style != AttrStyle::Outer {
-            continue;
+            return None;
         }
 
         let attr_name = attr
@@ -272,16 +2632,198 @@ fn attributes(attrs: &[Attribute]) -> Vec<FieldAttribute> {
             .expect("attribute is badly formatted");
 
         if attr_name.ident != "sqlx_binder" {
-            continue;
+            return None;
+        }
+
+        match &attr.meta {
+            Meta::List(list) => Some(list),
+            _ => None,
         }
+    })
+}
 
-        if let Meta::List(list) = &attr.meta {
-            match list.parse_args::<FieldAttributes>() {
-                Ok(items) => res.extend(items.attrs),
-                Err(e) => panic!("Error parsing field attributes: {}", e),
+/// Convert `PascalCase` struct name to `snake_case` table name
+fn snake_case(text: &str) -> String {
+    // The first character is never prepended with an underscore, so skip it even if it is an
+    // uppercase ASCII character.
+    let underscore_count = text
+        .chars()
+        .skip(1)
+        .filter(|&c| c.is_ascii_uppercase())
+        .count();
+    let mut result = String::with_capacity(text.len() + underscore_count);
+
+    for (i, c) in text.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i != 0 {
+                result.push('_');
             }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
         }
     }
 
-    res
+    result
+}
+
+/// `FieldEnum` or `FieldRef` type of a `flatten` field's binder struct, ex: `Address` to `AddressFieldEnum`
+fn flatten_type(ty: &Type, suffix: &str, lifetime: Option<syn::Lifetime>) -> Type {
+    let mut ty = ty.clone();
+    let Type::Path(path) = &mut ty else {
+        panic!("`flatten` field type must be a struct deriving `MySqlBinder`");
+    };
+    let segment = path
+        .path
+        .segments
+        .last_mut()
+        .expect("`flatten` field type must have a name");
+    segment.ident = format_ident!("{}{}", segment.ident, suffix);
+    if let Some(lifetime) = lifetime {
+        if segment.arguments.is_none() {
+            segment.arguments = syn::PathArguments::AngleBracketed(syn::parse_quote!(<>));
+        }
+        if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+            args.args
+                .insert(0, syn::GenericArgument::Lifetime(lifetime));
+        }
+    }
+    ty
+}
+
+/// `PartialEq` and `PartialOrd` of a `FieldEnum` or `FieldRef` with `json` variants, the same as derived ones
+/// except that `json` values are compared by their serialized JSON (so the user crate needs `serde_json`)
+fn json_comparisons(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    variants: Vec<(&syn::Ident, &Type, bool)>,
+    phantom: bool,
+) -> impl ToTokens {
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for (_, ty, json) in &variants {
+        where_clause.predicates.push(match json {
+            true => syn::parse_quote!(#ty: serde::Serialize),
+            false => syn::parse_quote!(#ty: PartialOrd),
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let idents = variants.iter().map(|(ident, _, _)| ident).collect::<Vec<_>>();
+    let indexes = 0..variants.len();
+    let (eqs, cmps): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .map(|(_, _, json)| match json {
+            true => (
+                quote! { json(l).zip(json(r)).is_some_and(|(l, r)| l == r) },
+                quote! { json(l).zip(json(r)).and_then(|(l, r)| l.partial_cmp(&r)) },
+            ),
+            false => (quote! { l == r }, quote! { l.partial_cmp(r) }),
+        })
+        .unzip();
+    let phantom_index = phantom.then(|| quote! { #name::__phantom(_, never) => match *never {}, });
+    quote! {
+        impl #impl_generics PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                fn json<__V: serde::Serialize>(value: &__V) -> Option<String> {
+                    serde_json::to_string(value).ok()
+                }
+                match (self, other) {
+                    #((#name::#idents(l), #name::#idents(r)) => #eqs,)*
+                    _ => false,
+                }
+            }
+        }
+
+        impl #impl_generics PartialOrd for #name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                fn json<__V: serde::Serialize>(value: &__V) -> Option<String> {
+                    serde_json::to_string(value).ok()
+                }
+                let index = |value: &Self| match value {
+                    #(#name::#idents(_) => #indexes,)*
+                    #phantom_index
+                };
+                match (self, other) {
+                    #((#name::#idents(l), #name::#idents(r)) => #cmps,)*
+                    _ => index(self).partial_cmp(&index(other)),
+                }
+            }
+        }
+    }
+}
+
+/// Parse `bind_with` or `decode_with` function path
+fn function_path(index: usize, val: &str) -> syn::Path {
+    syn::parse_str::<syn::Path>(val)
+        .unwrap_or_else(|_| panic!("field {} function '{}' is not a valid path", index, val))
+}
+
+/// Convert `snake_case` field name by `rename_all` rule,
+/// a result that is not a plain identifier (`kebab-case`) is quoted with backticks.
+fn rename_column(rule: &str, name: &str) -> String {
+    let pascal = || {
+        name.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                    None => String::new(),
+                }
+            })
+            .collect::<String>()
+    };
+    let column = match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(c) => c.to_lowercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+        "snake_case" => name.to_string(),
+        "SCREAMING_SNAKE_CASE" => name.to_uppercase(),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_uppercase().replace('_', "-"),
+        _ => panic!("unknown `rename_all` rule '{}'", rule),
+    };
+    if column.contains('-') {
+        format!("`{}`", column)
+    } else {
+        column
+    }
+}
+
+fn is_integer(ty: &Type) -> bool {
+    const INTEGERS: [&str; 12] = [
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+    ];
+    match ty {
+        Type::Path(path) => path
+            .path
+            .get_ident()
+            .is_some_and(|id| INTEGERS.iter().any(|i| id == i)),
+        _ => false,
+    }
+}
+
+fn is_bool(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.path.is_ident("bool"),
+        _ => false,
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
 }
@@ -1,3 +1,19 @@
+#[cfg(test)]
+mod common {
+
+    use std::time::Duration;
+
+    use sqlx::{MySql, Pool, mysql::MySqlPoolOptions};
+
+    /// Pool that never connects, generated methods fail with `PoolTimedOut`
+    pub fn lazy_pool() -> Pool<MySql> {
+        MySqlPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(1))
+            .connect_lazy("mysql://root@127.0.0.1:1/db")
+            .unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests_simple_struct {
 
@@ -235,3 +251,1798 @@ mod tests_rename {
         assert_eq!(field_enums[3], RenamerFieldEnum::life_expectancy(9));
     }
 }
+
+#[cfg(test)]
+mod tests_version {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    use crate::common::lazy_pool;
+
+    #[derive(MySqlBinder)]
+    struct Versioned {
+        id: u32,
+        name: String,
+        #[sqlx_binder(version)]
+        version: u32,
+    }
+
+    #[tokio::test]
+    async fn test_version() {
+        assert_eq!(
+            &*Versioned::update_sql(false, "id", "versioned", "db", "").unwrap(),
+            "UPDATE db.versioned SET name=?,version=version+1 WHERE id=? AND version=?;"
+        );
+
+        let pool = lazy_pool();
+        let mut row = Versioned {
+            id: 1,
+            name: "Taro".to_string(),
+            version: 7,
+        };
+        let err = row.update("id", None, "", &[], &pool, "db").await;
+        assert!(matches!(
+            err,
+            Err(VersionedBinderError::Sqlx(sqlx::Error::PoolTimedOut))
+        ));
+        // only bumped after a successful update
+        assert_eq!(row.version, 7);
+
+        let err = VersionedBinderError::VersionConflict;
+        assert_eq!(err.to_string(), "version conflict on 'Versioned'");
+        let err: VersionedBinderError = sqlx::Error::RowNotFound.into();
+        assert!(matches!(
+            err,
+            VersionedBinderError::Sqlx(sqlx::Error::RowNotFound)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests_guard {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    use crate::common::lazy_pool;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Guarded {
+        id: u32,
+        name: String,
+        #[sqlx_binder(guard)]
+        create_user: String,
+        #[sqlx_binder(guard, rename = "tenant")]
+        tenant_id: u32,
+        #[sqlx_binder(version)]
+        version: u32,
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(
+            &*Guarded::update_sql(false, "id", "guarded", "db", "").unwrap(),
+            "UPDATE db.guarded SET name=?,version=version+1 WHERE id=? AND version=? AND create_user=? AND tenant=?;"
+        );
+        assert_eq!(
+            &*Guarded::delete_sql("id", "guarded", "db").unwrap(),
+            "DELETE FROM db.guarded WHERE id=? AND create_user=? AND tenant=?;"
+        );
+        assert!(matches!(
+            Guarded::delete_sql("tenant_id", "guarded", "db"),
+            Err(sqlx::Error::ColumnNotFound(column)) if column == "tenant_id"
+        ));
+    }
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(tenant = "tenant_id", patch)]
+    struct Acc {
+        id: u32,
+        name: String,
+        #[sqlx_binder(guard)]
+        owner: String,
+        #[sqlx_binder(version)]
+        version: u32,
+        tenant_id: String,
+    }
+
+    #[tokio::test]
+    async fn test_guard_patch() {
+        // update_patch checks `version` and `guard` columns like update
+        assert_eq!(
+            &*Acc::update_patch_sql("id", &["name"], "acc", "db").unwrap(),
+            "UPDATE db.acc SET name=?,version=version+1 WHERE id=? AND version=? AND owner=? AND tenant_id=?;"
+        );
+        assert!(matches!(
+            Acc::update_patch_sql("acc_id", &["name"], "acc", "db"),
+            Err(sqlx::Error::ColumnNotFound(column)) if column == "acc_id"
+        ));
+
+        let mut acc = Acc {
+            id: 1,
+            name: "alice".to_string(),
+            owner: "bob".to_string(),
+            version: 3,
+            tenant_id: String::new(),
+        };
+        let patch = AccPatch {
+            name: Some("carol".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            acc.update_patch("id", &patch, None, "7", &lazy_pool(), "db")
+                .await,
+            Err(AccBinderError::Sqlx(sqlx::Error::PoolTimedOut))
+        ));
+        assert_eq!(acc.version, 3);
+        assert_eq!(acc.name, "alice");
+        assert!(
+            acc.update_patch("id", &AccPatch::default(), None, "7", &lazy_pool(), "db")
+                .await
+                .is_ok()
+        );
+        assert_eq!(acc.version, 3);
+        assert_eq!((acc.id, acc.owner.as_str()), (1, "bob"));
+    }
+}
+
+#[cfg(test)]
+mod tests_timestamp {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Stamped {
+        id: u32,
+        name: String,
+        #[sqlx_binder(created_at)]
+        create_datetime: Option<sqlx::types::time::OffsetDateTime>,
+        #[sqlx_binder(updated_at, rename = "update_datetime")]
+        updated: Option<sqlx::types::time::OffsetDateTime>,
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let row = Stamped {
+            id: 1,
+            name: "Taro".to_string(),
+            create_datetime: None,
+            updated: None,
+        };
+        assert_eq!(
+            &*row
+                .insert_sql(false, Some("id"), "stamped", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.stamped (name,create_datetime,update_datetime) VALUE (?,NOW(),NOW());"
+        );
+        assert_eq!(
+            &*Stamped::update_sql(false, "id", "stamped", "db", "").unwrap(),
+            "UPDATE db.stamped SET name=?,update_datetime=NOW() WHERE id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_audit {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Audited {
+        id: u32,
+        name: String,
+        #[sqlx_binder(created_by)]
+        create_user: String,
+        #[sqlx_binder(updated_by)]
+        update_user: String,
+    }
+
+    #[test]
+    fn test_audit() {
+        let row = Audited {
+            id: 1,
+            name: "Taro".to_string(),
+            create_user: "admin".to_string(),
+            update_user: "admin".to_string(),
+        };
+        // without an actor the audit columns are bound from the struct like any other field
+        assert_eq!(
+            &*row
+                .insert_sql(false, Some("id"), "audited", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.audited (name,create_user,update_user) VALUE (?,?,?);"
+        );
+        assert!(row.insert_binds("create_user", false, Some("id")));
+        assert_eq!(
+            &*Audited::update_sql(false, "id", "audited", "db", "").unwrap(),
+            "UPDATE db.audited SET name=?,update_user=? WHERE id=?;"
+        );
+        // with an actor they move to the end, where `actor` is bound
+        assert_eq!(
+            &*row
+                .insert_sql(true, Some("id"), "audited", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.audited (name,create_user,update_user) VALUE (?,?,?);"
+        );
+        assert!(!row.insert_binds("create_user", true, Some("id")));
+        assert!(!row.insert_binds("update_user", true, Some("id")));
+        assert_eq!(
+            &*Audited::update_sql(true, "id", "audited", "db", "").unwrap(),
+            "UPDATE db.audited SET name=?,update_user=? WHERE id=?;"
+        );
+        assert!(!Audited::update_binds("update_user", true, "id"));
+    }
+}
+
+#[cfg(test)]
+mod tests_soft_delete {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(patch)]
+    struct Flagged {
+        id: u32,
+        name: String,
+        #[sqlx_binder(soft_delete)]
+        deleted: bool,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Stamped {
+        id: u32,
+        #[sqlx_binder(soft_delete)]
+        delete_datetime: Option<sqlx::types::time::OffsetDateTime>,
+    }
+
+    #[test]
+    fn test_soft_delete() {
+        assert_eq!(
+            &*Flagged::soft_delete_sql(true, "id", "flagged", "db").unwrap(),
+            "UPDATE db.flagged SET deleted=1 WHERE id=?;"
+        );
+        assert_eq!(
+            &*Flagged::soft_delete_sql(false, "id", "flagged", "db").unwrap(),
+            "UPDATE db.flagged SET deleted=0 WHERE id=?;"
+        );
+
+        // update and update_patch never undelete the row
+        assert_eq!(
+            &*Flagged::update_sql(false, "id", "flagged", "db", "").unwrap(),
+            "UPDATE db.flagged SET name=? WHERE id=?;"
+        );
+        let patch = FlaggedPatch {
+            id: None,
+            name: Some("Jiro".to_string()),
+        };
+        assert_eq!(Flagged::patch_columns(&patch), vec!["name"]);
+
+        assert_eq!(
+            &*Stamped::soft_delete_sql(true, "id", "stamped", "db").unwrap(),
+            "UPDATE db.stamped SET delete_datetime=NOW() WHERE id=?;"
+        );
+        assert_eq!(
+            &*Stamped::soft_delete_sql(false, "id", "stamped", "db").unwrap(),
+            "UPDATE db.stamped SET delete_datetime=NULL WHERE id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_tenant {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(tenant = "tenant_id")]
+    struct Tenanted {
+        id: u32,
+        name: String,
+        tenant_id: u32,
+        #[sqlx_binder(soft_delete)]
+        deleted: bool,
+    }
+
+    #[test]
+    fn test_tenant() {
+        let row = Tenanted {
+            id: 1,
+            name: "Taro".to_string(),
+            tenant_id: 7,
+            deleted: false,
+        };
+        // the tenant column is always bound to the `tenant` argument, never to the field
+        assert_eq!(
+            &*row
+                .insert_sql(false, Some("id"), "tenanted", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.tenanted (name,deleted,tenant_id) VALUE (?,?,?);"
+        );
+        assert!(!row.insert_binds("tenant_id", false, Some("id")));
+        assert_eq!(
+            &*Tenanted::update_sql(false, "id", "tenanted", "db", "").unwrap(),
+            "UPDATE db.tenanted SET name=? WHERE id=? AND tenant_id=?;"
+        );
+        assert_eq!(
+            &*Tenanted::soft_delete_sql(true, "id", "tenanted", "db").unwrap(),
+            "UPDATE db.tenanted SET deleted=1 WHERE id=? AND tenant_id=?;"
+        );
+        assert_eq!(
+            &*Tenanted::delete_sql("id", "tenanted", "db").unwrap(),
+            "DELETE FROM db.tenanted WHERE id=? AND tenant_id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_generics {
+
+    use std::borrow::Cow;
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Page<'a, T>
+    where
+        T: Copy,
+    {
+        id: u32,
+        title: Cow<'a, str>,
+        value: T,
+        #[sqlx_binder(skip)]
+        marker: Option<&'a T>,
+    }
+
+    #[allow(dead_code)]
+    async fn insert_page(
+        page: &Page<'_, i64>,
+        pool: &Pool<MySql>,
+    ) -> sqlx::Result<MySqlQueryResult> {
+        page.insert(Some("id"), None, "", "", &[], pool, "db").await
+    }
+
+    #[test]
+    fn test_generics() {
+        let title = String::from("Taro");
+        let page = Page {
+            id: 1,
+            title: Cow::Borrowed(&title),
+            value: 3_i64,
+            marker: None,
+        };
+        assert_eq!(page.get_struct_name_snake(), "page");
+        assert_eq!(page.get_field_names(), vec!["id", "title", "value"]);
+        let field_enums = page.get_field_enums();
+        assert_eq!(field_enums[1], PageFieldEnum::title(Cow::Borrowed("Taro")));
+        assert_eq!(field_enums[2], PageFieldEnum::value(3));
+    }
+}
+
+#[cfg(test)]
+mod tests_field_ref {
+
+    use sqlx::{Arguments, Execute, MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref)]
+    struct Blob {
+        name: String,
+        #[sqlx_binder(rename = "payload")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn test_field_ref() {
+        let blob = Blob {
+            name: "Taro".to_string(),
+            data: vec![1, 2, 3],
+        };
+        let field_refs = blob.get_field_refs();
+        assert_eq!(field_refs[0], BlobFieldRef::name(&blob.name));
+        assert_eq!(field_refs[1], BlobFieldRef::data(&blob.data));
+        assert!(std::ptr::eq(
+            match blob.get_ref("data").unwrap() {
+                BlobFieldRef::data(p) => p,
+                _ => unreachable!(),
+            },
+            &blob.data
+        ));
+        assert!(blob.get_ref("payload").is_err());
+
+        let mut query: sqlx::query::Query<'_, sqlx::MySql, sqlx::mysql::MySqlArguments> =
+            sqlx::query("INSERT INTO blob VALUES (?,?)");
+        for param in field_refs {
+            query = param.bind(query);
+        }
+        assert_eq!(query.take_arguments().unwrap().unwrap().len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod tests_tuple_struct {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref)]
+    struct Pair(
+        #[sqlx_binder(rename = "dog_id")] u32,
+        #[sqlx_binder(rename = "owner_name")] String,
+        #[sqlx_binder(skip)] bool,
+    );
+
+    #[allow(dead_code)]
+    async fn insert_pair(pair: &Pair, pool: &Pool<MySql>) -> sqlx::Result<MySqlQueryResult> {
+        pair.insert(None, None, "", "", &[], pool, "db").await?;
+        pair.delete("dog_id", None, pool, "db").await
+    }
+
+    #[test]
+    fn test_tuple_struct() {
+        let pair = Pair(1, "Taro".to_string(), true);
+        assert_eq!(pair.get_struct_name_snake(), "pair");
+        assert_eq!(pair.get_field_names(), vec!["dog_id", "owner_name"]);
+        let field_enums = pair.get_field_enums();
+        assert_eq!(field_enums[0], PairFieldEnum::dog_id(1));
+        assert_eq!(
+            field_enums[1],
+            PairFieldEnum::owner_name("Taro".to_string())
+        );
+        assert_eq!(
+            pair.get_enum("owner_name").unwrap(),
+            PairFieldEnum::owner_name("Taro".to_string())
+        );
+        assert_eq!(pair.get_field_refs()[0], PairFieldRef::dog_id(&1));
+    }
+}
+
+#[cfg(test)]
+mod tests_rename_all {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(rename_all = "camelCase")]
+    struct Camel {
+        dog_name: String,
+        #[sqlx_binder(rename = "AGE")]
+        dog_age: u32,
+        life_expectancy: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(rename_all = "PascalCase")]
+    struct Pascal {
+        dog_name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Screaming {
+        dog_name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(rename_all = "kebab-case")]
+    struct Kebab {
+        dog_name: String,
+    }
+
+    #[test]
+    fn test_rename_all() {
+        let camel = Camel {
+            dog_name: "Taro".to_string(),
+            dog_age: 3,
+            life_expectancy: 9,
+        };
+        assert_eq!(
+            camel.get_field_names(),
+            vec!["dogName", "AGE", "lifeExpectancy"]
+        );
+        assert_eq!(
+            camel.get_enum("dog_age").unwrap(),
+            CamelFieldEnum::dog_age(3)
+        );
+        assert_eq!(
+            &*Camel::update_sql(false, "dogName", "camel", "db", "").unwrap(),
+            "UPDATE db.camel SET AGE=?,lifeExpectancy=? WHERE dogName=?;"
+        );
+
+        let name = "Taro".to_string();
+        let pascal = Pascal {
+            dog_name: name.clone(),
+        };
+        assert_eq!(
+            &*pascal
+                .insert_sql(false, None, "pascal", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.pascal (DogName) VALUE (?);"
+        );
+        let screaming = Screaming {
+            dog_name: name.clone(),
+        };
+        assert_eq!(
+            &*screaming
+                .insert_sql(false, None, "screaming", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.screaming (DOG_NAME) VALUE (?);"
+        );
+        let kebab = Kebab { dog_name: name };
+        assert_eq!(
+            &*kebab
+                .insert_sql(false, None, "kebab", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.kebab (`dog-name`) VALUE (?);"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_consts {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct DogOwner {
+        name: String,
+        #[sqlx_binder(rename = "year")]
+        age: u32,
+        #[sqlx_binder(skip)]
+        sex: String,
+    }
+
+    const _: () = assert!(DogOwner::FIELD_COUNT == 2);
+
+    #[test]
+    fn test_consts() {
+        assert_eq!(DogOwner::FIELD_NAMES, &["name", "year"]);
+        assert_eq!(DogOwner::TABLE_NAME, "dog_owner");
+        assert_eq!(DogOwner::FIELD_COUNT, 2);
+
+        let owner = DogOwner {
+            name: "Taro".to_string(),
+            age: 30,
+            sex: "male".to_string(),
+        };
+        assert_eq!(owner.get_field_names(), DogOwner::FIELD_NAMES);
+        assert_eq!(owner.get_struct_name_snake(), DogOwner::TABLE_NAME);
+        assert_eq!(owner.get_field_enums().len(), DogOwner::FIELD_COUNT);
+    }
+}
+
+#[cfg(test)]
+mod tests_cached_sql {
+
+    use std::sync::{Arc, OnceLock};
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Cached {
+        id: u32,
+    }
+
+    static STATEMENT: OnceLock<(Vec<String>, Arc<str>)> = OnceLock::new();
+
+    #[test]
+    fn test_cached_sql() {
+        // the first key of a statement is kept in its own slot
+        let first = Cached::cached_sql(&STATEMENT, &["delete", "id", "cached", "db"], || {
+            "DELETE FROM db.cached WHERE id=?;".to_string()
+        });
+        let second = Cached::cached_sql(&STATEMENT, &["delete", "id", "cached", "db"], || {
+            unreachable!("SQL is built only once")
+        });
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&STATEMENT.get().unwrap().1, &first));
+
+        // other keys go through the shared cache
+        let other = Cached::cached_sql(&STATEMENT, &["delete", "id", "cached", "db2"], || {
+            "DELETE FROM db2.cached WHERE id=?;".to_string()
+        });
+        assert_eq!(&*other, "DELETE FROM db2.cached WHERE id=?;");
+        let again = Cached::cached_sql(&STATEMENT, &["delete", "id", "cached", "db2"], || {
+            unreachable!("SQL is built only once")
+        });
+        assert!(Arc::ptr_eq(&other, &again));
+    }
+}
+
+#[cfg(test)]
+mod tests_write_only {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Order {
+        id: u32,
+        #[sqlx_binder(insert_only)]
+        external_ref: String,
+        #[sqlx_binder(update_only)]
+        note: String,
+        #[sqlx_binder(readonly)]
+        total: u32,
+    }
+
+    #[test]
+    fn test_write_only() {
+        let order = Order {
+            id: 1,
+            external_ref: "A-1".to_string(),
+            note: "paid".to_string(),
+            total: 100,
+        };
+        assert_eq!(
+            &*order
+                .insert_sql(false, Some("id"), "order", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.order (external_ref) VALUE (?);"
+        );
+        assert_eq!(
+            &*Order::update_sql(false, "id", "order", "db", "").unwrap(),
+            "UPDATE db.order SET note=? WHERE id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_generated {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Invoice {
+        id: u32,
+        amount: u32,
+        #[sqlx_binder(generated)]
+        amount_with_vat: u32,
+        #[sqlx_binder(db_default)]
+        status: String,
+        #[sqlx_binder(skip)]
+        cache: Option<String>,
+    }
+
+    #[test]
+    fn test_generated() {
+        let invoice = Invoice {
+            id: 1,
+            amount: 100,
+            amount_with_vat: 107,
+            status: "draft".to_string(),
+            cache: None,
+        };
+        assert_eq!(
+            Invoice::FIELD_NAMES,
+            &["id", "amount", "amount_with_vat", "status"]
+        );
+        assert_eq!(
+            &*invoice
+                .insert_sql(false, Some("id"), "invoice", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.invoice (amount) VALUE (?);"
+        );
+        assert_eq!(
+            &*Invoice::update_sql(false, "id", "invoice", "db", "").unwrap(),
+            "UPDATE db.invoice SET amount=? WHERE id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_skip_if_none {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Account {
+        id: u32,
+        name: String,
+        #[sqlx_binder(skip_if_none)]
+        plan: Option<String>,
+        note: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(skip_if_none)]
+    struct Profile {
+        id: u32,
+        bio: Option<String>,
+        age: Option<u8>,
+    }
+
+    #[test]
+    fn test_skip_if_none() {
+        let mut account = Account {
+            id: 1,
+            name: "alice".to_string(),
+            plan: None,
+            note: None,
+        };
+        // `note` has no `skip_if_none`, it is inserted as NULL
+        assert_eq!(
+            &*account
+                .insert_sql(false, Some("id"), "account", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.account (name,note) VALUE (?,?);"
+        );
+        account.plan = Some("pro".to_string());
+        assert_eq!(
+            &*account
+                .insert_sql(false, Some("id"), "account", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.account (name,plan,note) VALUE (?,?,?);"
+        );
+
+        let profile = Profile {
+            id: 2,
+            bio: Some("hello".to_string()),
+            age: None,
+        };
+        assert_eq!(
+            &*profile
+                .insert_sql(false, Some("id"), "profile", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.profile (bio) VALUE (?);"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_patch {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(patch_serde)]
+    struct Dog {
+        id: u32,
+        name: String,
+        age: u32,
+        nickname: Option<String>,
+        #[sqlx_binder(created_at)]
+        create_datetime: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    async fn update_patch(
+        dog: &Dog,
+        patch: &DogPatch,
+        pool: &Pool<MySql>,
+    ) -> sqlx::Result<MySqlQueryResult> {
+        dog.update_patch("id", patch, None, pool, "db").await
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut dog = Dog {
+            id: 1,
+            name: "Tom".to_string(),
+            age: 3,
+            nickname: Some("Tommy".to_string()),
+            create_datetime: None,
+        };
+        let patch = DogPatch {
+            age: Some(4),
+            nickname: Some(None),
+            ..Default::default()
+        };
+        dog.apply_patch(&patch);
+        assert_eq!(dog.id, 1);
+        assert_eq!(dog.name, "Tom");
+        assert_eq!(dog.age, 4);
+        assert_eq!(dog.nickname, None);
+        assert!(dog.create_datetime.is_none());
+        assert_eq!(patch.id, None);
+        assert_eq!(patch.name, None);
+    }
+
+    #[test]
+    fn test_patch_serde() {
+        let patch: DogPatch = serde_json::from_str(r#"{"name":"Jerry"}"#).unwrap();
+        assert_eq!(patch.name.as_deref(), Some("Jerry"));
+        assert_eq!(patch.age, None);
+        assert_eq!(
+            serde_json::to_string(&patch).unwrap(),
+            r#"{"name":"Jerry"}"#
+        );
+        assert_eq!(patch.nickname, None);
+
+        // a present `null` clears the column, a missing key leaves it alone
+        let patch: DogPatch = serde_json::from_str(r#"{"nickname":null}"#).unwrap();
+        assert_eq!(patch.nickname, Some(None));
+        assert_eq!(Dog::patch_columns(&patch), vec!["nickname"]);
+        assert_eq!(
+            serde_json::to_string(&patch).unwrap(),
+            r#"{"nickname":null}"#
+        );
+        let patch: DogPatch = serde_json::from_str(r#"{"nickname":"Tom"}"#).unwrap();
+        assert_eq!(patch.nickname, Some(Some("Tom".to_string())));
+    }
+}
+
+#[cfg(test)]
+mod tests_bind_with {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult, mysql::MySqlRow};
+    use sqlx_binder::MySqlBinder;
+
+    /// neither `Encode` nor `Decode` for MySQL
+    #[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+    pub struct Cents(i64);
+
+    fn cents_to_i64(cents: &Cents) -> i64 {
+        cents.0
+    }
+
+    fn i64_to_cents(value: i64) -> Result<Cents, String> {
+        if value < 0 {
+            return Err(format!("negative price {}", value));
+        }
+        Ok(Cents(value))
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref)]
+    struct Product {
+        id: u32,
+        #[sqlx_binder(bind_with = "cents_to_i64", decode_with = "i64_to_cents")]
+        price: Cents,
+        #[sqlx_binder(skip)]
+        cache: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    async fn insert_and_decode(
+        product: &Product,
+        row: &MySqlRow,
+        pool: &Pool<MySql>,
+    ) -> sqlx::Result<MySqlQueryResult> {
+        let decoded = Product::from_row(row)?;
+        assert!(decoded.cache.is_none());
+        product
+            .insert(Some("id"), None, "", "", &[], pool, "db")
+            .await
+    }
+
+    #[test]
+    fn test_bind_with() {
+        let product = Product {
+            id: 1,
+            price: Cents(1999),
+            cache: None,
+        };
+        assert_eq!(Product::FIELD_NAMES, &["id", "price"]);
+        assert_eq!(
+            product.get_field_enums()[1],
+            ProductFieldEnum::price(Cents(1999))
+        );
+        assert_eq!(
+            product.get_ref("price"),
+            Ok(ProductFieldRef::price(&Cents(1999)))
+        );
+    }
+
+    #[test]
+    fn test_decode_with() {
+        assert!(matches!(
+            Product::map_decode_error("price", i64_to_cents(1999)),
+            Ok(Cents(1999))
+        ));
+        match Product::map_decode_error("price", i64_to_cents(-1)) {
+            Err(sqlx::Error::ColumnDecode { index, source }) => {
+                assert_eq!(index, "price");
+                assert_eq!(source.to_string(), "negative price -1");
+            }
+            other => panic!("unexpected {:?}", other.map(|cents| cents.0)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests_json {
+
+    use serde::{Deserialize, Serialize};
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult, mysql::MySqlRow};
+    use sqlx_binder::MySqlBinder;
+
+    /// neither `PartialEq` nor `PartialOrd`
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Config {
+        theme: String,
+        retries: u32,
+    }
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref, patch)]
+    struct Setting {
+        id: u32,
+        #[sqlx_binder(json)]
+        config: Config,
+        #[sqlx_binder(json)]
+        tags: Option<Vec<String>>,
+        #[sqlx_binder(json)]
+        extra: serde_json::Value,
+    }
+
+    #[allow(dead_code)]
+    fn decode(row: &MySqlRow) -> sqlx::Result<Setting> {
+        Setting::from_row(row)
+    }
+
+    #[test]
+    fn test_json() {
+        let setting = Setting {
+            id: 1,
+            config: Config {
+                theme: "dark".to_string(),
+                retries: 3,
+            },
+            tags: None,
+            extra: serde_json::json!({ "beta": true }),
+        };
+        assert_eq!(Setting::FIELD_NAMES, &["id", "config", "tags", "extra"]);
+        let config = |retries| Config {
+            theme: "dark".to_string(),
+            retries,
+        };
+
+        // json values are compared by their serialized JSON
+        let field_enums = setting.get_field_enums();
+        assert_eq!(field_enums[1], SettingFieldEnum::config(config(3)));
+        assert_ne!(field_enums[1], SettingFieldEnum::config(config(4)));
+        assert!(field_enums[1] < SettingFieldEnum::config(config(4)));
+        assert!(SettingFieldEnum::id(2) < field_enums[1]);
+        assert_eq!(field_enums[2], SettingFieldEnum::tags(None));
+        assert_eq!(
+            setting.get_ref("extra"),
+            Ok(SettingFieldRef::extra(&serde_json::json!({ "beta": true })))
+        );
+        assert!(setting.get_field_refs()[0] < SettingFieldRef::extra(&setting.extra));
+
+        let patch = SettingPatch {
+            config: Some(config(3)),
+            ..Default::default()
+        };
+        assert_eq!(patch.clone(), patch);
+        assert_ne!(patch, SettingPatch::default());
+        assert_eq!(
+            &*setting
+                .insert_sql(false, Some("id"), "setting", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.setting (config,tags,extra) VALUE (?,?,?);"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "stream"))]
+mod tests_stream {
+
+    use futures_core::Stream;
+    use sqlx::{
+        Arguments, MySql, Pool,
+        mysql::{MySqlArguments, MySqlQueryResult},
+    };
+    use sqlx_binder::MySqlBinder;
+
+    use crate::common::lazy_pool;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter)]
+    #[sqlx_binder(tenant = "tenant_id")]
+    struct Dog {
+        id: u32,
+        name: String,
+        tenant_id: String,
+    }
+
+    fn assert_stream<S: Stream<Item = sqlx::Result<Dog>> + Send>(stream: S) -> S {
+        stream
+    }
+
+    #[allow(dead_code)]
+    async fn stream_dogs(pool: &Pool<MySql>) -> sqlx::Result<Vec<String>> {
+        let mut conn = pool.acquire().await?;
+        let filter = Dog::filter().col().name().like("T%");
+        let mut stream = assert_stream(Dog::stream_all(
+            &filter,
+            Some("tbl_dog"),
+            "tenant_a",
+            &mut *conn,
+            "db",
+        ));
+        let mut dogs = Vec::new();
+        while let Some(dog) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            let dog = dog?;
+            dogs.push(format!("{} {} {}", dog.id, dog.name, dog.tenant_id));
+        }
+        Ok(dogs)
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let pool = lazy_pool();
+        // the stream owns its SQL, the filter and the other arguments can be dropped before polling
+        let mut stream = {
+            let filter = Dog::filter().col().name().eq("Tom".to_string());
+            let tenant = "tenant_a".to_string();
+            let db_name = "db".to_string();
+            Dog::stream_all(&filter, None, &tenant, &pool, &db_name)
+        };
+        let mut rows = Vec::new();
+        while let Some(row) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            rows.push(row);
+        }
+        assert!(matches!(rows[..], [Err(sqlx::Error::PoolTimedOut)]));
+        assert!(
+            std::future::poll_fn(|cx| stream.as_mut().poll_next(cx))
+                .await
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_stream_query() {
+        let filter = Dog::filter()
+            .col()
+            .name()
+            .eq("Tom".to_string())
+            .or()
+            .col()
+            .id()
+            .gt(3);
+        let (sql, arguments) = Dog::stream_all_query(&filter, "dog", "tenant_a", "db").unwrap();
+        assert_eq!(
+            sql,
+            "SELECT id,name,tenant_id FROM db.dog WHERE (name=? OR id>?) AND tenant_id=?;"
+        );
+        // values of the filter in order, then the tenant
+        let mut expected = MySqlArguments::default();
+        expected.add("Tom".to_string()).unwrap();
+        expected.add(3_u32).unwrap();
+        expected.add("tenant_a").unwrap();
+        assert_eq!(format!("{:?}", arguments), format!("{:?}", expected));
+    }
+}
+
+#[cfg(test)]
+mod tests_flatten {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult, mysql::MySqlRow};
+    use sqlx_binder::MySqlBinder;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref)]
+    pub struct Address {
+        street: String,
+        city: String,
+    }
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(field_ref)]
+    struct Shop {
+        id: u32,
+        #[sqlx_binder(flatten, prefix = "address_")]
+        address: Address,
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    async fn insert_and_decode(
+        shop: &Shop,
+        row: &MySqlRow,
+        pool: &Pool<MySql>,
+    ) -> sqlx::Result<MySqlQueryResult> {
+        Shop::from_row(row)?;
+        shop.insert(Some("id"), None, "", "", &[], pool, "db")
+            .await?;
+        shop.update("id", None, "", &[], pool, "db").await
+    }
+
+    #[test]
+    fn test_flatten() {
+        let shop = Shop {
+            id: 1,
+            address: Address {
+                street: "Main".to_string(),
+                city: "Bangkok".to_string(),
+            },
+            name: "Pet Shop".to_string(),
+        };
+        assert_eq!(Shop::FIELD_COUNT, 4);
+        assert_eq!(
+            Shop::field_names(),
+            &["id", "address_street", "address_city", "name"]
+        );
+        assert_eq!(
+            shop.get_field_enums(),
+            vec![
+                ShopFieldEnum::id(1),
+                ShopFieldEnum::address(AddressFieldEnum::street("Main".to_string())),
+                ShopFieldEnum::address(AddressFieldEnum::city("Bangkok".to_string())),
+                ShopFieldEnum::name("Pet Shop".to_string()),
+            ]
+        );
+        assert_eq!(
+            shop.get_enum("address_city"),
+            Ok(ShopFieldEnum::address(AddressFieldEnum::city(
+                "Bangkok".to_string()
+            )))
+        );
+        assert_eq!(
+            shop.get_ref("address_street"),
+            Ok(ShopFieldRef::address(AddressFieldRef::street(
+                &"Main".to_string()
+            )))
+        );
+        assert_eq!(shop.get_field_refs().len(), Shop::FIELD_COUNT);
+        assert!(shop.get_enum("address_zip").is_err());
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(rename_all = "kebab-case")]
+    pub struct Location {
+        street_name: String,
+        #[sqlx_binder(readonly)]
+        zip_code: String,
+        #[sqlx_binder(insert_only)]
+        country: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    struct Branch {
+        id: u32,
+        #[sqlx_binder(flatten, prefix = "loc_")]
+        location: Location,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    pub struct Versioned {
+        #[sqlx_binder(version)]
+        version: u32,
+    }
+
+    // structs with attributes that `flatten` can not apply are refused at compile time
+    const _: () = assert!(!Location::__FLATTEN_UNSUPPORTED);
+    const _: () = assert!(Versioned::__FLATTEN_UNSUPPORTED);
+
+    #[test]
+    fn test_flatten_attributes() {
+        let branch = Branch {
+            id: 1,
+            location: Location {
+                street_name: "Main".to_string(),
+                zip_code: "10110".to_string(),
+                country: "TH".to_string(),
+            },
+        };
+        assert_eq!(
+            Branch::field_names(),
+            &["id", "`loc_street-name`", "`loc_zip-code`", "loc_country"]
+        );
+        // the nested struct's `readonly` and `insert_only` columns are skipped like its own
+        assert_eq!(
+            &*branch
+                .insert_sql(false, Some("id"), "branch", "db", "", "")
+                .unwrap(),
+            "INSERT INTO db.branch (`loc_street-name`,loc_country) VALUE (?,?);"
+        );
+        assert_eq!(
+            &*Branch::update_sql(false, "id", "branch", "db", "").unwrap(),
+            "UPDATE db.branch SET `loc_street-name`=? WHERE id=?;"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_binder_enum {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult, mysql::MySqlRow};
+    use sqlx_binder::{BinderEnum, MySqlBinder};
+
+    #[derive(BinderEnum, Debug, PartialEq, PartialOrd, Clone)]
+    #[sqlx_binder(rename_all = "snake_case")]
+    pub enum OrderStatus {
+        Draft,
+        #[sqlx_binder(rename = "paid_in_full")]
+        Paid,
+        Cancelled,
+    }
+
+    #[derive(BinderEnum, Debug, PartialEq, PartialOrd, Clone, Copy)]
+    #[sqlx_binder(as_i32)]
+    pub enum Priority {
+        Low = 1,
+        Normal,
+        High = 10,
+    }
+
+    #[derive(MySqlBinder)]
+    struct Order {
+        id: u32,
+        status: OrderStatus,
+        priority: Priority,
+    }
+
+    #[allow(dead_code)]
+    async fn insert_and_decode(
+        order: &Order,
+        row: &MySqlRow,
+        pool: &Pool<MySql>,
+    ) -> sqlx::Result<MySqlQueryResult> {
+        let decoded = Order::from_row(row)?;
+        assert_eq!(decoded.status, OrderStatus::Draft);
+        order
+            .insert(Some("id"), None, "", "", &[], pool, "db")
+            .await
+    }
+
+    #[test]
+    fn test_binder_enum() {
+        assert_eq!(OrderStatus::Draft.as_str(), "draft");
+        assert_eq!(OrderStatus::Paid.as_str(), "paid_in_full");
+        assert_eq!(
+            OrderStatus::try_from_str("cancelled"),
+            Ok(OrderStatus::Cancelled)
+        );
+        assert_eq!(
+            OrderStatus::try_from_str("Paid"),
+            Err(
+                "invalid OrderStatus 'Paid', expected one of draft, paid_in_full, cancelled"
+                    .to_string()
+            )
+        );
+
+        assert_eq!(Priority::Low.as_i32(), 1);
+        assert_eq!(Priority::Normal.as_i32(), 2);
+        assert_eq!(Priority::High.as_i32(), 10);
+        assert_eq!(Priority::try_from_i32(2), Ok(Priority::Normal));
+        assert_eq!(
+            Priority::try_from_i32(3),
+            Err("invalid Priority 3, expected one of Low, Normal, High".to_string())
+        );
+
+        let order = Order {
+            id: 1,
+            status: OrderStatus::Paid,
+            priority: Priority::High,
+        };
+        assert_eq!(
+            order.get_field_enums(),
+            vec![
+                OrderFieldEnum::id(1),
+                OrderFieldEnum::status(OrderStatus::Paid),
+                OrderFieldEnum::priority(Priority::High),
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_filter {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    use crate::common::lazy_pool;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter, patch)]
+    struct Dog {
+        id: u32,
+        name: String,
+        age: u32,
+        nickname: Option<String>,
+        #[sqlx_binder(soft_delete)]
+        deleted_at: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    async fn select_and_write(pool: &Pool<MySql>) -> sqlx::Result<MySqlQueryResult> {
+        let filter = Dog::filter()
+            .col()
+            .age()
+            .gt(3)
+            .and()
+            .col()
+            .name()
+            .like("T%");
+        let dogs = Dog::select(&filter, None, pool, "db").await?;
+        assert_eq!(
+            dogs.len() as i64,
+            Dog::count(&filter, None, pool, "db").await?
+        );
+        let patch = DogPatch {
+            nickname: Some(Some("old".to_string())),
+            ..Default::default()
+        };
+        Dog::update_where(&filter, &patch, None, pool, "db").await?;
+        Dog::delete_where(&filter.with_deleted(), None, pool, "db").await
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = Dog::filter()
+            .col()
+            .age()
+            .gt(3)
+            .and()
+            .col()
+            .name()
+            .like("T%");
+        assert_eq!(filter.to_sql(), "age>? AND name LIKE ?");
+
+        let filter = Dog::filter().col().id().in_list([1, 2, 3]).group(
+            Dog::filter()
+                .col()
+                .nickname()
+                .is_null()
+                .or()
+                .col()
+                .age()
+                .le(1),
+        );
+        assert_eq!(
+            filter.to_sql(),
+            "id IN (?,?,?) AND (nickname IS NULL OR age<=?)"
+        );
+
+        assert_eq!(Dog::filter().col().id().in_list([]).to_sql(), "1=0");
+        assert_eq!(
+            Dog::filter()
+                .col()
+                .nickname()
+                .like("T%")
+                .or()
+                .col()
+                .name()
+                .ne("Tom".to_string())
+                .to_sql(),
+            "nickname LIKE ? OR name<>?"
+        );
+        assert!(Dog::filter().is_empty());
+
+        // rows like `dog`
+        let dog = Dog {
+            id: 1,
+            name: "Tom".to_string(),
+            age: 3,
+            nickname: None,
+            deleted_at: None,
+        };
+        let filter = Dog::filter()
+            .col()
+            .id()
+            .ne(dog.id)
+            .col()
+            .name()
+            .eq(dog.name)
+            .col()
+            .age()
+            .ge(dog.age)
+            .col()
+            .nickname()
+            .eq(dog.nickname)
+            .col()
+            .deleted_at()
+            .eq(dog.deleted_at);
+        assert_eq!(
+            filter.to_sql(),
+            "id<>? AND name=? AND age>=? AND nickname=? AND deleted_at=?"
+        );
+    }
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter, patch, tenant = "tenant_id")]
+    struct Pet {
+        id: u32,
+        name: String,
+        tenant_id: String,
+        #[sqlx_binder(soft_delete)]
+        deleted: bool,
+    }
+
+    #[test]
+    fn test_scope_where() {
+        // `or` conditions are kept together before `tenant` and `soft_delete` conditions
+        let filter = Pet::filter()
+            .col()
+            .id()
+            .eq(1)
+            .or()
+            .col()
+            .name()
+            .eq("Tom".to_string());
+        assert_eq!(
+            Pet::scope_where(filter.to_sql(), false),
+            " WHERE (id=? OR name=?) AND tenant_id=? AND deleted=0"
+        );
+        assert_eq!(
+            Pet::scope_where(filter.to_sql(), true),
+            " WHERE (id=? OR name=?) AND tenant_id=?"
+        );
+        assert_eq!(
+            Pet::scope_where("", false),
+            " WHERE tenant_id=? AND deleted=0"
+        );
+        assert_eq!(Dog::scope_where("", true), "");
+    }
+
+    #[tokio::test]
+    async fn test_empty_filter() {
+        let pool = lazy_pool();
+        let patch = PetPatch {
+            name: Some("Jerry".to_string()),
+            ..Default::default()
+        };
+        // an empty filter would write every row of the tenant, it is refused before connecting
+        assert!(matches!(
+            Pet::update_where(&Pet::filter(), &patch, None, "7", &pool, "db").await,
+            Err(sqlx::Error::InvalidArgument(message)) if message == "update_where with an empty filter"
+        ));
+        assert!(matches!(
+            Pet::delete_where(&Pet::filter().with_deleted(), None, "7", &pool, "db").await,
+            Err(sqlx::Error::InvalidArgument(message)) if message == "delete_where with an empty filter"
+        ));
+        let filter = Pet::filter().col().id().eq(1);
+        assert!(matches!(
+            Pet::update_where(&filter, &patch, None, "7", &pool, "db").await,
+            Err(sqlx::Error::PoolTimedOut)
+        ));
+        assert!(matches!(
+            Pet::delete_where(&filter, None, "7", &pool, "db").await,
+            Err(sqlx::Error::PoolTimedOut)
+        ));
+    }
+
+    /// fields named like methods of `member_binder::Filter`
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter)]
+    struct Member {
+        id: u32,
+        group: String,
+        or: u32,
+        new: bool,
+    }
+
+    #[test]
+    fn test_filter_method_names() {
+        let member = Member {
+            id: 1,
+            group: "admin".to_string(),
+            or: 2,
+            new: true,
+        };
+        let filter = Member::filter()
+            .col()
+            .group()
+            .eq(member.group)
+            .or()
+            .col()
+            .or()
+            .gt(member.or)
+            .group(
+                Member::filter()
+                    .col()
+                    .new()
+                    .eq(member.new)
+                    .and()
+                    .col()
+                    .id()
+                    .ne(member.id),
+            );
+        assert_eq!(filter.to_sql(), "group=? OR or>? AND (new=? AND id<>?)");
+    }
+
+    /// `filter` types of `Purchase` are in `purchase_binder`, `PurchaseOrder` is free
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter)]
+    struct Purchase {
+        id: u32,
+        total: u32,
+    }
+
+    #[derive(MySqlBinder)]
+    struct PurchaseOrder {
+        id: u32,
+        purchase_id: u32,
+    }
+
+    #[test]
+    fn test_filter_type_names() {
+        let purchase = Purchase { id: 1, total: 100 };
+        let order = PurchaseOrder {
+            id: 2,
+            purchase_id: purchase.id,
+        };
+        let filter: purchase_binder::Filter = Purchase::filter()
+            .col()
+            .id()
+            .eq(order.purchase_id)
+            .col()
+            .total()
+            .ge(purchase.total);
+        assert_eq!(filter.to_sql(), "id=? AND total>=?");
+        assert_eq!(purchase_binder::Column::total.as_str(), "total");
+        assert_eq!(order.get_field_enums()[0], PurchaseOrderFieldEnum::id(2));
+    }
+}
+
+#[cfg(test)]
+mod tests_page {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter)]
+    struct Dog {
+        id: u32,
+        name: String,
+        #[sqlx_binder(rename = "dog_age")]
+        age: u32,
+    }
+
+    use dog_binder::{Column, Order, Page};
+
+    #[allow(dead_code)]
+    async fn select_pages(pool: &Pool<MySql>) -> sqlx::Result<i64> {
+        let filter = Dog::filter().col().age().gt(3);
+        let page = Page::Offset {
+            limit: 20,
+            offset: 40,
+        };
+        let order_by = [(Column::age, Order::Desc)];
+        let (dogs, total) =
+            Dog::select_page(&filter, Column::id, &order_by, &page, None, pool, "db").await?;
+        let page = Page::Keyset {
+            after: dogs.last().map(|dog| DogFieldEnum::id(dog.id)),
+            limit: 20,
+        };
+        let (dogs, _) = Dog::select_page(&filter, Column::id, &[], &page, None, pool, "db").await?;
+        assert!(dogs.iter().all(|dog| !dog.name.is_empty() && dog.age > 3));
+        Ok(total)
+    }
+
+    #[test]
+    fn test_page() {
+        assert_eq!(Column::age.as_str(), "dog_age");
+        let filter = Dog::filter().col().age().gt(3);
+        let sql = |order_by: &[(Column, Order)], page: &Page| {
+            Dog::select_page_sql(&filter, Column::id, order_by, page, "dog", "db")
+                .map(|(sql, after, limit, offset)| (sql, after.is_some(), limit, offset))
+        };
+
+        // the primary key breaks ties of `order_by`, and is the default order
+        let page = Page::Offset {
+            limit: 20,
+            offset: 40,
+        };
+        assert_eq!(
+            sql(&[(Column::age, Order::Desc)], &page).unwrap(),
+            (
+                "SELECT id,name,dog_age FROM db.dog WHERE (dog_age>?) ORDER BY dog_age DESC,id ASC LIMIT ? OFFSET ?;"
+                    .to_string(),
+                false,
+                20,
+                40
+            )
+        );
+        assert_eq!(
+            sql(&[], &page).unwrap().0,
+            "SELECT id,name,dog_age FROM db.dog WHERE (dog_age>?) ORDER BY id ASC LIMIT ? OFFSET ?;"
+        );
+
+        // keyset pages continue after a primary key value
+        let page = Page::Keyset {
+            after: Some(DogFieldEnum::id(7)),
+            limit: 10,
+        };
+        assert_eq!(
+            sql(&[(Column::id, Order::Desc)], &page).unwrap(),
+            (
+                "SELECT id,name,dog_age FROM db.dog WHERE (dog_age>?) AND id<? ORDER BY id DESC LIMIT ? OFFSET ?;"
+                    .to_string(),
+                true,
+                10,
+                0
+            )
+        );
+        assert_eq!(
+            sql(
+                &[],
+                &Page::Keyset {
+                    after: None,
+                    limit: 10
+                }
+            )
+            .unwrap()
+            .0,
+            "SELECT id,name,dog_age FROM db.dog WHERE (dog_age>?) ORDER BY id ASC LIMIT ? OFFSET ?;"
+        );
+        assert!(matches!(
+            sql(&[(Column::age, Order::Asc)], &page),
+            Err(sqlx::Error::InvalidArgument(_))
+        ));
+        let page = Page::Keyset {
+            after: Some(DogFieldEnum::age(3)),
+            limit: 10,
+        };
+        assert!(matches!(
+            sql(&[], &page),
+            Err(sqlx::Error::InvalidArgument(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests_exists {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    #[allow(dead_code)]
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(filter)]
+    #[sqlx_binder(tenant = "tenant_id")]
+    struct Dog {
+        id: u32,
+        name: String,
+        tenant_id: String,
+        #[sqlx_binder(soft_delete)]
+        deleted: bool,
+    }
+
+    #[allow(dead_code)]
+    async fn exists_and_count(pool: &Pool<MySql>) -> sqlx::Result<bool> {
+        let total = Dog::count(&Dog::filter(), None, "tenant_a", pool, "db").await?;
+        let exists = Dog::exists_by(
+            DogFieldEnum::name("Tom".to_string()),
+            None,
+            "tenant_a",
+            pool,
+            "db",
+        )
+        .await?;
+        let deleted =
+            Dog::exists_by_with_deleted(DogFieldEnum::id(1), None, "tenant_a", pool, "db").await?;
+        Ok(exists || deleted || total > 0)
+    }
+
+    #[test]
+    fn test_exists() {
+        assert_eq!(
+            &*Dog::exists_by_sql("id", false, "dog", "db"),
+            "SELECT EXISTS(SELECT 1 FROM db.dog WHERE (id=?) AND tenant_id=? AND deleted=0);"
+        );
+        assert_eq!(
+            &*Dog::exists_by_sql("id", true, "dog", "db"),
+            "SELECT EXISTS(SELECT 1 FROM db.dog WHERE (id=?) AND tenant_id=?);"
+        );
+        assert_eq!(
+            &*Dog::exists_by_sql("name", false, "tbl_dog", "db"),
+            "SELECT EXISTS(SELECT 1 FROM db.tbl_dog WHERE (name=?) AND tenant_id=? AND deleted=0);"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_strict {
+
+    use sqlx::{MySql, Pool, mysql::MySqlQueryResult};
+    use sqlx_binder::MySqlBinder;
+
+    use crate::common::lazy_pool;
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(strict)]
+    struct Dog {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(MySqlBinder)]
+    #[sqlx_binder(strict)]
+    struct Versioned {
+        id: u32,
+        #[sqlx_binder(version)]
+        version: u32,
+    }
+
+    #[tokio::test]
+    async fn test_strict() {
+        let pool = lazy_pool();
+        let dog = Dog {
+            id: 1,
+            name: "Tom".to_string(),
+        };
+        // the transaction is begun first, nothing is built or executed without it
+        assert!(matches!(
+            dog.update_strict("id", None, "", &[], &pool, "db").await,
+            Err(DogBinderError::Sqlx(sqlx::Error::PoolTimedOut))
+        ));
+        assert!(matches!(
+            dog.delete_strict("id", None, &pool, "db").await,
+            Err(DogBinderError::Sqlx(sqlx::Error::PoolTimedOut))
+        ));
+
+        let mut versioned = Versioned { id: 1, version: 2 };
+        assert!(matches!(
+            versioned
+                .update_strict("id", None, "", &[], &pool, "db")
+                .await,
+            Err(VersionedBinderError::Sqlx(sqlx::Error::PoolTimedOut))
+        ));
+        assert_eq!(versioned.version, 2);
+        assert!(
+            versioned
+                .update("id", None, "", &[], &pool, "db")
+                .await
+                .is_err()
+        );
+        assert_eq!(versioned.version, 2);
+
+        assert_eq!(
+            DogBinderError::NotFound.to_string(),
+            "no row of 'Dog' found"
+        );
+        assert_eq!(
+            DogBinderError::TooManyRows(2).to_string(),
+            "2 rows of 'Dog' affected, expected 1"
+        );
+        assert!(matches!(
+            DogBinderError::from(sqlx::Error::RowNotFound),
+            DogBinderError::Sqlx(sqlx::Error::RowNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_expect_one_row() {
+        // commit_one_row commits only on exactly one affected row and rolls back otherwise
+        assert!(matches!(
+            Dog::__expect_one_row(0),
+            Err(DogBinderError::NotFound)
+        ));
+        assert!(Dog::__expect_one_row(1).is_ok());
+        assert!(matches!(
+            Dog::__expect_one_row(2),
+            Err(DogBinderError::TooManyRows(2))
+        ));
+    }
+}